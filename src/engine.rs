@@ -1,12 +1,20 @@
+use crate::intern::{Interner, KeyId, ValueId};
 use crate::types::*;
-use ascent::ascent;
-use pod2::frontend::AnchoredKey;
+use ascent::{ascent, ascent_source};
+#[cfg(feature = "parallel")]
+use ascent::ascent_par;
+use ascent::lattice::Dual;
+use pod2::frontend::{AnchoredKey, Origin};
 use pod2::middleware::{hash_str, NativeOperation, Value as MiddlewareValue};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{debug, debug_span, instrument, trace, trace_span, warn, Level};
 
 use super::types::WildcardStatement;
 
-// Helper function to convert HashableValue to Value
-fn to_value(hv: &HashableValue) -> MiddlewareValue {
+// Helper function to convert HashableValue to Value. Native operations compare
+// entries by this value, so Bool(true) and Int(1) are equal.
+pub(crate) fn to_value(hv: &HashableValue) -> MiddlewareValue {
     match hv {
         HashableValue::Int(i) => MiddlewareValue::from(*i),
         HashableValue::String(s) => MiddlewareValue::from(hash_str(s)),
@@ -18,8 +26,9 @@ fn to_value(hv: &HashableValue) -> MiddlewareValue {
 }
 
 // Helper function to check if one value contains another
-// Supports arrays and sets, returns false for other types
-fn check_contains(container: &HashableValue, contained: &HashableValue) -> bool {
+// Supports arrays and sets, returns false for other types; a failed set lookup
+// is an error, not absence
+pub(crate) fn check_contains(container: &HashableValue, contained: &HashableValue) -> Result<bool, String> {
     match (container, contained) {
         // For arrays, check if the contained value is an element
         (HashableValue::Array(arr), value) => {
@@ -27,446 +36,1619 @@ fn check_contains(container: &HashableValue, contained: &HashableValue) -> bool
             // Check each element in the array using the iterator
             for (_, elem) in arr.iter() {
                 if elem == &value {
-                    return true;
+                    return Ok(true);
                 }
             }
-            false
+            Ok(false)
         },
         
         // For sets, check if the contained value is a member
         (HashableValue::Set(set), value) => {
             let value = to_value(value);
-            set.contains(&value).unwrap_or(false)
+            set.contains(&value).map_err(|e| e.to_string())
         },
         
         // For other types, containment is not defined
-        _ => false,
+        _ => Ok(false),
+    }
+}
+
+// Helper function to check that a container does not hold a value
+// Only defined for arrays and sets, returns false for other types
+pub(crate) fn check_not_contains(container: &HashableValue, contained: &HashableValue) -> Result<bool, String> {
+    match container {
+        HashableValue::Array(_) | HashableValue::Set(_) => check_contains(container, contained).map(|found| !found),
+        _ => Ok(false),
+    }
+}
+
+// What an operand's value must be for a statement over it to be well typed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Expected {
+    Int,
+    Container,
+}
+
+impl Expected {
+    fn admits(self, value: &HashableValue) -> bool {
+        match self {
+            Expected::Int => matches!(value, HashableValue::Int(_)),
+            Expected::Container => matches!(
+                value,
+                HashableValue::Array(_) | HashableValue::Set(_) | HashableValue::Dictionary(_)
+            ),
+        }
+    }
+
+    fn describe(self) -> &'static str {
+        match self {
+            Expected::Int => "an integer",
+            Expected::Container => "an array, set or dictionary",
+        }
+    }
+}
+
+// The operands of a statement whose values are constrained, and how
+fn expected_types(stmt: &HashableStatement) -> Vec<(&AnchoredKey, Expected)> {
+    match stmt {
+        HashableStatement::Gt(key1, key2) | HashableStatement::Lt(key1, key2) => {
+            vec![(key1, Expected::Int), (key2, Expected::Int)]
+        },
+        HashableStatement::SumOf(key1, key2, key3)
+        | HashableStatement::ProductOf(key1, key2, key3)
+        | HashableStatement::MaxOf(key1, key2, key3) => {
+            vec![(key1, Expected::Int), (key2, Expected::Int), (key3, Expected::Int)]
+        },
+        HashableStatement::Contains(container, _) | HashableStatement::NotContains(container, _) => {
+            vec![(container, Expected::Container)]
+        },
+        _ => vec![],
+    }
+}
+
+// The same for a target's operands
+fn expected_operand_types(target: &WildcardStatement) -> Vec<(WildcardAnchoredKey, Expected)> {
+    match target {
+        WildcardStatement::Gt(key1, key2) | WildcardStatement::Lt(key1, key2) => {
+            vec![(key1.clone(), Expected::Int), (key2.clone(), Expected::Int)]
+        },
+        WildcardStatement::SumOf(key1, key2, key3)
+        | WildcardStatement::ProductOf(key1, key2, key3)
+        | WildcardStatement::MaxOf(key1, key2, key3) => vec![
+            (key1.clone(), Expected::Int),
+            (key2.clone().into(), Expected::Int),
+            (key3.clone().into(), Expected::Int),
+        ],
+        WildcardStatement::Contains(container, _) | WildcardStatement::NotContains(container, _) => {
+            vec![(container.clone(), Expected::Container)]
+        },
+        _ => vec![],
+    }
+}
+
+// Candidate proofs for one query statement, with the bindings each one implies
+type QueryCandidates = Vec<(HashableStatement, DeductionChain, Bindings)>;
+
+// Extend a partial query proof with one candidate per remaining statement,
+// keeping only combinations whose named wildcards agree
+fn join_candidates(candidates: &[QueryCandidates], partial: QueryProof, results: &mut Vec<QueryProof>) {
+    let Some((first, rest)) = candidates.split_first() else {
+        results.push(partial);
+        return;
+    };
+    for (stmt, chain, bindings) in first {
+        let conflicts = bindings.iter()
+            .any(|(name, binding)| matches!(partial.bindings.get(name), Some(bound) if bound.conflicts_with(binding)));
+        if conflicts {
+            continue;
+        }
+        let mut next = partial.clone();
+        next.statements.push(stmt.clone());
+        for step in chain {
+            if !next.chain.contains(step) {
+                next.chain.push(step.clone());
+            }
+        }
+        for (name, binding) in bindings {
+            next.bindings.entry(name.clone()).or_insert_with(|| binding.clone());
+        }
+        join_candidates(rest, next, results);
+    }
+}
+
+// The cheapest derivation chosen for each derived statement: its proof cost,
+// operation and premises
+type Derivations = HashMap<HashableStatement, (usize, u8, Vec<HashableStatement>)>;
+
+// Statements with no chosen derivation are known (or unestablished) and cost nothing
+fn proof_cost(derivations: &Derivations, stmt: &HashableStatement) -> usize {
+    derivations.get(stmt).map_or(0, |(cost, _, _)| *cost)
+}
+
+// The chain of the proof graph of `statement`, or None if it relies on a premise
+// nothing establishes. The rules never record such a derivation, so this is
+// reported rather than handed out as a chain that would not check.
+fn flatten(statement: &HashableStatement, graph: &ProofGraph) -> Option<DeductionChain> {
+    match graph.to_chain() {
+        Ok(chain) => Some(chain),
+        Err(premise) => {
+            warn!(statement = %statement, premise = %premise, "dropping a proof with an unestablished premise");
+            None
+        },
+    }
+}
+
+// Whether a statement is an entry of the pod being built, as literal operands are
+fn is_self_entry(stmt: &HashableStatement) -> bool {
+    matches!(stmt, HashableStatement::ValueOf(key, _) if key.0 == self_origin())
+}
+
+// The left-hand key of a comparison or equality
+fn compared_key(stmt: &HashableStatement) -> Option<&AnchoredKey> {
+    match stmt {
+        HashableStatement::Equal(key, _)
+        | HashableStatement::Gt(key, _)
+        | HashableStatement::Lt(key, _) => Some(key),
+        _ => None,
+    }
+}
+
+// How long a bounded run goes between checks of its budget
+const BUDGET_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+// Drop the proofs whose chains are longer than the budget allows
+fn drop_long_chains(budget: &Budget, proofs: &mut Vec<Proof>) -> Option<Limit> {
+    let max = budget.max_chain_length?;
+    let before = proofs.len();
+    proofs.retain(|proof| proof.chain.len() <= max);
+    (proofs.len() < before).then_some(Limit::ChainLength)
+}
+
+// How much of the fact base the engine explores when proving
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EvaluationMode {
+    // Compute the equality closure over every key in the fact base
+    #[default]
+    Exhaustive,
+    // Only explore keys reachable from the targets' concrete keys
+    DemandDriven,
+}
+
+// Which ascent runtime evaluates the rules
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Backend {
+    #[default]
+    Sequential,
+    // Evaluate the rules on rayon's global thread pool with `ascent_par!`
+    #[cfg(feature = "parallel")]
+    Parallel,
+}
+
+// The ascent program behind an engine
+pub(crate) enum Program {
+    Sequential(AscentProgram),
+    #[cfg(feature = "parallel")]
+    Parallel(ParAscentProgram),
+}
+
+// Evaluate an expression against whichever program backs the engine. Both
+// programs are generated from the same rules and so have the same relation
+// fields; relations are read by index because their container types differ.
+macro_rules! with_program {
+    ($program:expr, $prog:ident => $body:expr) => {
+        match $program {
+            Program::Sequential($prog) => $body,
+            #[cfg(feature = "parallel")]
+            Program::Parallel($prog) => $body,
+        }
+    };
+}
+
+impl Program {
+    fn new(mode: EvaluationMode, backend: Backend) -> Self {
+        let mut program = match backend {
+            Backend::Sequential => Program::Sequential(AscentProgram::default()),
+            #[cfg(feature = "parallel")]
+            Backend::Parallel => Program::Parallel(ParAscentProgram::default()),
+        };
+        with_program!(&mut program, prog => { prog.match_counter.push((MatchCounter::default(),)); });
+        if mode == EvaluationMode::Exhaustive {
+            with_program!(&mut program, prog => { prog.exhaustive_mode.push(()); });
+        }
+        program
+    }
+
+    fn run(&mut self) {
+        with_program!(self, prog => prog.run())
+    }
+
+    // Run for at most `timeout`; true if the fixpoint was reached. A run that
+    // stops early is picked up again by the next run.
+    fn run_timeout(&mut self, timeout: Duration) -> bool {
+        with_program!(self, prog => prog.run_timeout(timeout))
+    }
+
+    // Take the candidate matches the program's rules counted, on whichever
+    // threads they ran
+    fn take_matches(&self) -> usize {
+        with_program!(self, prog => prog.match_counter[0].0.take())
+    }
+
+    // Tuples across every relation
+    fn tuple_count(&self) -> usize {
+        self.relation_sizes().values().sum()
+    }
+
+    // Tuples in each relation, by name
+    fn relation_sizes(&self) -> BTreeMap<&'static str, usize> {
+        with_program!(self, prog => BTreeMap::from([
+            ("known_statement", prog.known_statement.len()),
+            ("target_statement", prog.target_statement.len()),
+            ("can_prove", prog.can_prove.len()),
+            ("fact", prog.fact.len()),
+            ("derivation", prog.derivation.len()),
+            ("interned_key", prog.interned_key.len()),
+            ("known_value", prog.known_value.len()),
+            ("value_by_key", prog.value_by_key.len()),
+            ("value_by_origin", prog.value_by_origin.len()),
+            ("key_name", prog.key_name.len()),
+            ("prefix_length", prog.prefix_length.len()),
+            ("name_prefix", prog.name_prefix.len()),
+            ("target_operand", prog.target_operand.len()),
+            ("operand_value", prog.operand_value.len()),
+            ("known_equal", prog.known_equal.len()),
+            ("known_gt", prog.known_gt.len()),
+            ("known_lt", prog.known_lt.len()),
+            ("known_neq", prog.known_neq.len()),
+            ("known_contains", prog.known_contains.len()),
+            ("known_not_contains", prog.known_not_contains.len()),
+            ("exhaustive_mode", prog.exhaustive_mode.len()),
+            ("equality_root", prog.equality_root.len()),
+            ("demanded_key", prog.demanded_key.len()),
+            ("equal_edge", prog.equal_edge.len()),
+            ("reachable_equal", prog.reachable_equal.len()),
+            ("equal_chain", prog.equal_chain.len()),
+            ("connected_to_target", prog.connected_to_target.len()),
+            ("container_error", prog.container_error.len()),
+        ]))
+    }
+
+    // Fixpoint iterations run so far, over all strata
+    fn rounds(&self) -> usize {
+        with_program!(self, prog => prog.scc_iters.iter().sum())
+    }
+
+    fn can_prove(&self) -> Vec<&(WildcardStatement, HashableStatement)> {
+        with_program!(self, prog => (0..prog.can_prove.len()).map(|i| &prog.can_prove[i]).collect())
+    }
+
+    fn targets(&self) -> Vec<&WildcardStatement> {
+        with_program!(self, prog => (0..prog.target_statement.len()).map(|i| &prog.target_statement[i].0).collect())
+    }
+
+    fn container_errors(&self) -> Vec<&(WildcardStatement, KeyId, String)> {
+        with_program!(self, prog => (0..prog.container_error.len()).map(|i| &prog.container_error[i]).collect())
+    }
+
+    fn derivations(&self) -> Vec<&(HashableStatement, u8, Vec<HashableStatement>)> {
+        with_program!(self, prog => (0..prog.derivation.len()).map(|i| &prog.derivation[i]).collect())
     }
 }
 
 // Main deduction engine that handles proof generation
 pub struct DeductionEngine {
-    prog: AscentProgram,
+    prog: Program,
+    mode: EvaluationMode,
+    backend: Backend,
+    // Statements added with `add_fact`, which proofs copy in from their source pods
+    known: HashSet<HashableStatement>,
+    // The targets `prove` and `prove_best` report on. The program keeps every
+    // target it has been given, and what it derived for them, so replaced
+    // targets are filtered out of its results rather than removed.
+    targets: Vec<WildcardStatement>,
+    // The known value of each key, and the known statements that constrain the
+    // type of each key's value, for checking facts as they arrive
+    values: HashMap<AnchoredKey, HashableValue>,
+    typed_facts: HashMap<AnchoredKey, Vec<HashableStatement>>,
+    stats: ProveStats,
+    interner: Interner,
 }
 
 impl DeductionEngine {
     pub fn new() -> Self {
+        Self::with_mode(EvaluationMode::default())
+    }
+
+    pub fn with_mode(mode: EvaluationMode) -> Self {
+        Self::with_backend(mode, Backend::default())
+    }
+
+    pub fn with_backend(mode: EvaluationMode, backend: Backend) -> Self {
         Self {
-            prog: AscentProgram::default(),
+            prog: Program::new(mode, backend),
+            mode,
+            backend,
+            known: HashSet::new(),
+            targets: Vec::new(),
+            values: HashMap::new(),
+            typed_facts: HashMap::new(),
+            stats: ProveStats::default(),
+            interner: Interner::default(),
         }
     }
 
     // Reset the program's state
     pub fn reset(&mut self) {
-        self.prog = AscentProgram::default();
+        self.prog = Program::new(self.mode, self.backend);
+        self.known.clear();
+        self.targets.clear();
+        self.values.clear();
+        self.typed_facts.clear();
+        self.stats = ProveStats::default();
+        self.interner = Interner::default();
     }
 
-    // Add a known fact to the engine
-    pub fn add_fact(&mut self, fact: HashableStatement) {
-        self.prog.known_statement.push((fact,));
+    // Add a known fact to the engine. Facts (and targets) may be added after a
+    // call to `prove`: the program keeps its relations between runs, so there is
+    // no need to reset and re-add everything, and the next call proves from the
+    // old facts and the new ones together.
+    //
+    // A fact is rejected, and not added, if it is ill typed against the values
+    // already known: a comparison or arithmetic statement over a non-integer, or
+    // a containment statement over something that is not a container.
+    pub fn add_fact(&mut self, fact: HashableStatement) -> Result<(), ProverError> {
+        self.check_types(&fact)?;
+        if let HashableStatement::ValueOf(key, value) = &fact {
+            self.values.insert(key.clone(), value.clone());
+        }
+        for (key, _) in expected_types(&fact) {
+            self.typed_facts.entry(key.clone()).or_default().push(fact.clone());
+        }
+        self.known.insert(fact.clone());
+        // The program's relations work on interned keys and values
+        let interner = &mut self.interner;
+        with_program!(&mut self.prog, prog => {
+            match &fact {
+                HashableStatement::ValueOf(key, value) => { prog.known_value.push((interner.key(key), interner.value(value))); },
+                HashableStatement::Equal(key1, key2) => { prog.known_equal.push(interner.key_pair(key1, key2)); },
+                HashableStatement::Gt(key1, key2) => { prog.known_gt.push(interner.key_pair(key1, key2)); },
+                HashableStatement::Lt(key1, key2) => { prog.known_lt.push(interner.key_pair(key1, key2)); },
+                HashableStatement::NotEqual(key1, key2) => { prog.known_neq.push(interner.key_pair(key1, key2)); },
+                HashableStatement::Contains(key1, key2) => { prog.known_contains.push(interner.key_pair(key1, key2)); },
+                HashableStatement::NotContains(key1, key2) => { prog.known_not_contains.push(interner.key_pair(key1, key2)); },
+                _ => {},
+            }
+            prog.known_statement.push((fact,));
+        });
+        Ok(())
+    }
+
+    // Check a fact's operands against the values known for them, and a new value
+    // against the statements already known about its key
+    fn check_types(&self, fact: &HashableStatement) -> Result<(), ProverError> {
+        let ill_typed = |reason: String| ProverError::IllTypedFact { fact: fact.clone(), reason };
+        for (key, expected) in expected_types(fact) {
+            match self.values.get(key) {
+                Some(value) if !expected.admits(value) => {
+                    return Err(ill_typed(format!("{} holds {}, not {}", key.1, value, expected.describe())));
+                },
+                _ => {},
+            }
+        }
+        if let HashableStatement::ValueOf(key, value) = fact {
+            for known in self.typed_facts.get(key).into_iter().flatten() {
+                for (_, expected) in expected_types(known).into_iter().filter(|(typed_key, _)| *typed_key == key) {
+                    if !expected.admits(value) {
+                        return Err(ill_typed(format!("{} needs {} there", known, expected.describe())));
+                    }
+                }
+            }
+        }
+        Ok(())
     }
 
-    // Set the target statement we're trying to prove
+    // Set the target statement we're trying to prove, replacing any others
     pub fn set_target(&mut self, target: WildcardStatement) {
-        self.prog.target_statement = vec![(target,)];
+        self.targets.clear();
+        self.add_target(target);
     }
 
-    // Run the deduction engine and return all proofs found
-    pub fn prove(&mut self) -> Vec<(HashableStatement, DeductionChain)> {
-        self.prog.run();
-        self.prog.can_prove.clone()
+    // Add another target statement to prove in the same run
+    pub fn add_target(&mut self, target: WildcardStatement) {
+        self.demand(&target);
+        self.targets.push(target);
     }
 
-    // Print a human-readable proof chain
-    pub fn print_proof(&self, statement: HashableStatement, chain: DeductionChain) {
-        println!("\nProved: {}", statement);
-        if chain.is_empty() {
-            println!("This statement was directly known (no deduction needed)");
+    // Have the program prove `target`, without making it one of the engine's
+    // targets
+    fn demand(&mut self, target: &WildcardStatement) {
+        if self.prog.targets().contains(&target) {
             return;
         }
+        self.intern_target(target);
+        with_program!(&mut self.prog, prog => { prog.target_statement.push((target.clone(),)); });
+    }
 
-        println!("\nProof steps:");
-        for (step, (op_code, inputs, output)) in chain.iter().enumerate() {
-            println!("\nStep {}:", step + 1);
-            println!("Operation: {}", operation_name(*op_code));
-            println!("From:");
-            for input in inputs {
-                println!("  - {}", input);
+    // Intern the concrete keys a target mentions, and add its literal operands as
+    // the values of their self entries
+    fn intern_target(&mut self, target: &WildcardStatement) {
+        let mut concrete: Vec<AnchoredKey> = target.operands().iter()
+            .filter_map(|operand| operand.as_concrete())
+            .collect();
+        if let WildcardStatement::SumOf(_, key1, key2)
+        | WildcardStatement::ProductOf(_, key1, key2)
+        | WildcardStatement::MaxOf(_, key1, key2) = target {
+            concrete.extend([key1.clone(), key2.clone()]);
+        }
+        for key in concrete {
+            let id = self.interner.key(&key);
+            with_program!(&mut self.prog, prog => { prog.interned_key.push((key, id)); });
+        }
+        for operand in target.operands() {
+            if let (WildcardId::Literal(value), Some(key)) = (&operand.0, operand.as_concrete()) {
+                let row = (self.interner.key(&key), self.interner.value(value));
+                with_program!(&mut self.prog, prog => { prog.known_value.push(row); });
             }
-            println!("Deduced:");
-            println!("  => {}", output);
         }
     }
 
-    pub fn prove_multiple(&mut self, targets: Vec<WildcardStatement>) -> Vec<(HashableStatement, DeductionChain)> {
-        let mut all_proofs = Vec::new();
-        let mut remaining_targets = targets;
+    // Run the deduction engine and return one proof per statement proven for
+    // each target, using the statement's cheapest derivation. Use
+    // `prove_exhaustive` for a proof per derivation. Fails if a container lookup
+    // failed, or if no target could be proven.
+    #[instrument(level = "debug", skip_all)]
+    pub fn prove(&mut self) -> Result<Vec<Proof>, ProverError> {
+        self.run();
+        self.extract(|engine| engine.checked_proofs(Self::all_proofs))
+    }
 
-        // Store original known statements before we start
-        let original_known_statements: Vec<HashableStatement> = self.prog.known_statement.iter()
-            .map(|(stmt,)| stmt.clone())
-            .collect();
+    // Run the deduction engine and return a proof for every way each statement
+    // was proven for each target: a copy if the statement is known, and one per
+    // derivation the rules recorded for it, each with the cheapest proofs of its
+    // premises. Fails like `prove`.
+    #[instrument(level = "debug", skip_all)]
+    pub fn prove_exhaustive(&mut self) -> Result<Vec<Proof>, ProverError> {
+        self.run();
+        self.extract(|engine| engine.checked_proofs(Self::every_proof))
+    }
+
+    // The proofs `proofs` finds for the current targets, or why there are none
+    fn checked_proofs(&self, proofs: impl FnOnce(&Self) -> Vec<Proof>) -> Result<Vec<Proof>, ProverError> {
+        self.check_containers(&self.targets)?;
+        let proofs = proofs(self);
+        match self.targets.first() {
+            Some(target) if proofs.is_empty() => Err(self.diagnose(target)),
+            _ => Ok(proofs),
+        }
+    }
+
+    // Surface the first container lookup that failed for one of `targets`.
+    // Lookups for earlier calls' targets stay in the program but aren't reported.
+    fn check_containers(&self, targets: &[WildcardStatement]) -> Result<(), ProverError> {
+        let failed = self.prog.container_errors().into_iter()
+            .find(|(target, _, _)| targets.contains(target));
+        match failed {
+            Some((_, key, reason)) => Err(ProverError::ContainerLookup { key: key.get().clone(), reason: reason.clone() }),
+            None => Ok(()),
+        }
+    }
 
-        while !remaining_targets.is_empty() {
-            let mut new_remaining = Vec::new();
-            let mut proved_something = false;
-
-            println!("\nAttempting to prove {} remaining targets", remaining_targets.len());
-            // Try to prove each remaining target
-            for (i, target) in remaining_targets.iter().enumerate() {
-                println!("\nTrying to prove target {}: {:?}", i, target);
-                
-                // Reset the program's state before each attempt
-                self.reset();
-                
-                // Re-add original known statements
-                for stmt in &original_known_statements {
-                    self.add_fact(stmt.clone());
+    // Explain why a target has no proof: the rules can't prove targets like it,
+    // one of its operands has a value of the wrong type, or the facts just
+    // don't establish it
+    fn diagnose(&self, target: &WildcardStatement) -> ProverError {
+        for (operand, expected) in expected_operand_types(target) {
+            let Some(key) = operand.as_concrete() else {
+                continue;
+            };
+            let value = match &operand.0 {
+                WildcardId::Literal(value) => value,
+                _ => match self.values.get(&key) {
+                    Some(value) => value,
+                    None => continue,
+                },
+            };
+            if expected == Expected::Container && matches!(value, HashableValue::Dictionary(_)) {
+                return ProverError::UnsupportedTarget {
+                    target: target.clone(),
+                    reason: "containment is only proven for arrays and sets".to_string(),
+                };
+            }
+            if !expected.admits(value) {
+                return ProverError::IllTypedFact {
+                    reason: format!("{:?} needs {}", target, expected.describe()),
+                    fact: HashableStatement::ValueOf(key, value.clone()),
+                };
+            }
+        }
+        ProverError::NoProof(target.clone())
+    }
+
+    // Like `prove`, but stop at the first limit of `budget` that is hit and
+    // return the proofs found so far. Fails if a container lookup failed; finding
+    // no proofs is not an error, as the run may have stopped before finding any.
+    #[instrument(level = "debug", skip_all)]
+    pub fn prove_bounded(&mut self, budget: &Budget) -> Result<Bounded<Vec<Proof>>, ProverError> {
+        let limit = self.run_within(budget);
+        let mut proofs = self.extract(|engine| engine.check_containers(&engine.targets).map(|()| engine.all_proofs()))?;
+        let limit = limit.or(drop_long_chains(budget, &mut proofs));
+        Ok(Bounded::new(limit, proofs))
+    }
+
+    fn all_proofs(&self) -> Vec<Proof> {
+        let derivations = self.cheapest_derivations();
+        self.current_proofs().into_iter()
+            .filter_map(|(target, stmt)| self.to_proof(target, stmt, &derivations))
+            .collect()
+    }
+
+    fn every_proof(&self) -> Vec<Proof> {
+        let derivations = self.cheapest_derivations();
+        let mut alternatives: HashMap<&HashableStatement, Vec<(u8, &Vec<HashableStatement>)>> = HashMap::new();
+        for (stmt, operation, premises) in self.prog.derivations() {
+            alternatives.entry(stmt).or_default().push((*operation, premises));
+        }
+        let mut proofs = Vec::new();
+        for (target, stmt) in self.current_proofs() {
+            let bindings = target.bindings(stmt).unwrap_or_default();
+            if self.known.contains(stmt) {
+                let chain = vec![(NativeOperation::CopyStatement as u8, vec![stmt.clone()], stmt.clone())];
+                proofs.push(Proof { statement: stmt.clone(), chain, bindings: bindings.clone() });
+            }
+            for (operation, premises) in alternatives.get(stmt).into_iter().flatten() {
+                if let Some(chain) = self.chain_via(stmt, *operation, premises, &derivations) {
+                    proofs.push(Proof { statement: stmt.clone(), chain, bindings: bindings.clone() });
                 }
-                
-                // Re-add all previously proven facts
-                for (_, _, output) in all_proofs.iter().flat_map(|(_, chain): &(HashableStatement, DeductionChain)| chain.iter()) {
-                    self.add_fact(output.clone());
+            }
+        }
+        proofs
+    }
+
+    // The statements the program proved for the engine's current targets
+    fn current_proofs(&self) -> Vec<&(WildcardStatement, HashableStatement)> {
+        self.prog.can_prove().into_iter()
+            .filter(|(target, _)| self.targets.contains(target))
+            .collect()
+    }
+
+    // Run the program in slices, checking the budget between them. Returns the
+    // limit that stopped the run, or None once the fixpoint is reached.
+    fn run_within(&mut self, budget: &Budget) -> Option<Limit> {
+        let start = self.start_evaluation();
+        let limit = self.run_slices(budget);
+        self.finish_evaluation(start);
+        limit
+    }
+
+    fn run_slices(&mut self, budget: &Budget) -> Option<Limit> {
+        let start_rounds = self.prog.rounds();
+        let mut round = 0;
+        loop {
+            if budget.cancellation.as_ref().is_some_and(CancellationToken::is_cancelled) {
+                return Some(Limit::Cancelled);
+            }
+            let slice = match budget.deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Some(Limit::Deadline);
+                    }
+                    remaining.min(BUDGET_CHECK_INTERVAL)
+                },
+                None => BUDGET_CHECK_INTERVAL,
+            };
+            let _round = debug_span!("round", round).entered();
+            round += 1;
+            let before = self.match_counts();
+            let finished = self.prog.run_timeout(slice);
+            self.trace_matches(before);
+            if budget.max_tuples.is_some_and(|max| self.prog.tuple_count() > max) {
+                return Some(Limit::Tuples);
+            }
+            if budget.max_rounds.is_some_and(|max| self.prog.rounds() - start_rounds > max) {
+                return Some(Limit::Rounds);
+            }
+            if finished {
+                return None;
+            }
+        }
+    }
+
+    // Run the program to fixpoint
+    fn run(&mut self) {
+        let _round = debug_span!("round").entered();
+        let start = self.start_evaluation();
+        let before = self.match_counts();
+        self.prog.run();
+        self.trace_matches(before);
+        self.finish_evaluation(start);
+    }
+
+    // Where the counters stood when an evaluation started. Matches counted
+    // outside this engine's runs are dropped.
+    fn start_evaluation(&self) -> (Instant, usize) {
+        self.prog.take_matches();
+        (Instant::now(), self.prog.rounds())
+    }
+
+    // Replace the stats with those of the evaluation that just finished
+    fn finish_evaluation(&mut self, (start, rounds): (Instant, usize)) {
+        self.stats = ProveStats {
+            relation_sizes: self.prog.relation_sizes(),
+            rounds: self.prog.rounds() - rounds,
+            evaluation_time: start.elapsed(),
+            extraction_time: Duration::ZERO,
+            candidate_matches: self.prog.take_matches(),
+            interned_keys: self.interner.key_count(),
+            interned_values: self.interner.value_count(),
+        };
+    }
+
+    // Run the extraction phase of a call, which chooses derivations and builds
+    // proofs from the relations the evaluation left, and record its time
+    fn extract<T>(&mut self, extract: impl FnOnce(&Self) -> T) -> T {
+        let start = Instant::now();
+        let result = extract(self);
+        self.stats.extraction_time = start.elapsed();
+        result
+    }
+
+    // Statistics for the last proving call
+    pub fn stats(&self) -> &ProveStats {
+        &self.stats
+    }
+
+    // How many derivations and target matches the program holds. Relations
+    // only grow, so the tuples past these counts are the ones a run added.
+    fn match_counts(&self) -> (usize, usize) {
+        (self.prog.derivations().len(), self.prog.can_prove().len())
+    }
+
+    // Emit an event for every rule match a run added
+    fn trace_matches(&self, (derivations, proofs): (usize, usize)) {
+        let derived = self.prog.derivations();
+        let proven = self.prog.can_prove();
+        debug!(derivations = derived.len() - derivations, proofs = proven.len() - proofs, "run finished");
+        if !tracing::enabled!(Level::TRACE) {
+            return;
+        }
+        for (stmt, operation, premises) in derived.into_iter().skip(derivations) {
+            trace!(operation = step_name(*operation), statement = %stmt, premises = premises.len(), "derived");
+        }
+        for (target, stmt) in proven.into_iter().skip(proofs) {
+            trace!(target = ?target, statement = %stmt, "matched target");
+        }
+    }
+
+    // Run the deduction engine and return all proofs found for a specific target.
+    // Fails if a container lookup for the target failed; finding no proofs is
+    // not an error.
+    #[instrument(level = "debug", skip_all)]
+    pub fn proofs_for(&mut self, target: &WildcardStatement) -> Result<Vec<Proof>, ProverError> {
+        self.run();
+        self.extract(|engine| {
+            engine.check_containers(std::slice::from_ref(target))?;
+            let derivations = engine.cheapest_derivations();
+            Ok(engine.prog.can_prove().into_iter()
+                .filter(|(proved_target, _)| proved_target == target)
+                .filter_map(|(_, stmt)| engine.to_proof(target, stmt, &derivations))
+                .collect())
+        })
+    }
+
+    // Run the deduction engine and return one proof per proven statement, using
+    // its cheapest derivation, cheapest first. Fails like `proofs_for`.
+    #[instrument(level = "debug", skip_all)]
+    pub fn prove_best(&mut self) -> Result<Vec<Proof>, ProverError> {
+        self.run();
+        self.extract(|engine| {
+            engine.check_containers(&engine.targets)?;
+            let derivations = engine.cheapest_derivations();
+            let mut seen = HashSet::new();
+            // Bindings come from whichever target the statement was first proven for
+            let mut proofs: Vec<(usize, Proof)> = engine.current_proofs().into_iter()
+                .filter(|(_, stmt)| seen.insert(stmt.clone()))
+                .filter_map(|(target, stmt)| Some((proof_cost(&derivations, stmt), engine.to_proof(target, stmt, &derivations)?)))
+                .collect();
+            proofs.sort_by_key(|(cost, _)| *cost);
+            Ok(proofs.into_iter().map(|(_, proof)| proof).collect())
+        })
+    }
+
+    // Prove a conjunctive query: every statement is proven in the same run, then
+    // the per-statement proofs are joined on their named wildcards. Returns one
+    // combined proof per consistent binding. The statements are only targets for
+    // this call; the engine's own targets are left as they were. Fails if a
+    // container lookup for one of the statements failed.
+    #[instrument(level = "debug", skip_all, fields(statements = query.0.len()))]
+    pub fn prove_query(&mut self, query: &ConjunctiveQuery) -> Result<Vec<QueryProof>, ProverError> {
+        for target in &query.0 {
+            self.demand(target);
+        }
+        self.run();
+
+        self.extract(|engine| {
+            engine.check_containers(&query.0)?;
+            let derivations = engine.cheapest_derivations();
+            let candidates: Vec<QueryCandidates> = query.0.iter()
+                .map(|target| engine.query_candidates(target, &derivations))
+                .collect();
+
+            let mut results = Vec::new();
+            join_candidates(&candidates, QueryProof::default(), &mut results);
+            Ok(results)
+        })
+    }
+
+    // Prove a range target. Each end is proven by a strict comparison or, for an
+    // inclusive bound, by equality; the two are joined on the ranged key. Returns
+    // one proof per key in range, with the lower then upper statement. Like a
+    // query's statements, the comparisons are only targets for this call, and
+    // a failed container lookup for one of them fails the call.
+    #[instrument(level = "debug", skip_all)]
+    pub fn prove_range(&mut self, range: &RangeQuery) -> Result<Vec<QueryProof>, ProverError> {
+        let lower = range.lower_targets();
+        let upper = range.upper_targets();
+        for target in lower.iter().chain(&upper) {
+            self.demand(target);
+        }
+        self.run();
+
+        self.extract(|engine| {
+            engine.check_containers(&[lower.as_slice(), upper.as_slice()].concat())?;
+            let derivations = engine.cheapest_derivations();
+            let candidates: [QueryCandidates; 2] = [
+                lower.iter().flat_map(|target| engine.query_candidates(target, &derivations)).collect(),
+                upper.iter().flat_map(|target| engine.query_candidates(target, &derivations)).collect(),
+            ];
+            let mut results = Vec::new();
+            join_candidates(&candidates, QueryProof::default(), &mut results);
+            // Both ends must constrain the same anchored key, even when the key's
+            // wildcards bind nothing
+            results.retain(|proof| compared_key(&proof.statements[0]) == compared_key(&proof.statements[1]));
+            Ok(results)
+        })
+    }
+
+    // The proof DAG of a statement proven by the last run, or None if it was
+    // neither known nor proven
+    pub fn proof_graph(&self, statement: &HashableStatement) -> Option<ProofGraph> {
+        let proven = self.known.contains(statement)
+            || self.prog.can_prove().iter().any(|(_, stmt)| stmt == statement);
+        proven.then(|| self.build_graph(statement, &self.cheapest_derivations()))
+    }
+
+    // Pick the cheapest derivation of every derived statement. A proof costs one
+    // per deduction step in its tree and known statements cost nothing, so each
+    // relaxation round settles at least one more level of every tree. Costs only
+    // fall, and each premise of a chosen derivation is strictly cheaper than its
+    // conclusion, so the chosen derivations never form a cycle.
+    fn cheapest_derivations(&self) -> Derivations {
+        let derivations = self.prog.derivations();
+        let derived: HashSet<&HashableStatement> = derivations.iter().map(|(stmt, _, _)| stmt).collect();
+        let mut best = Derivations::new();
+        let mut round = 0;
+        loop {
+            let _round = trace_span!("relaxation", round).entered();
+            round += 1;
+            let mut changed = false;
+            for (stmt, operation, premises) in derivations.iter().copied() {
+                if self.known.contains(stmt) {
+                    continue;
                 }
-                
-                self.prog.target_statement = vec![(target.clone(),)];
-                
-                self.prog.run();
-                if let Some(proof) = self.prog.can_prove.first().cloned() {
-                    println!("Successfully proved target {}: {:?}", i, proof.0);
-                    // Successfully proved this target
-                    // Add all intermediate steps in the proof chain as facts
-                    for (_, _, output) in &proof.1 {
-                        println!("Adding intermediate fact: {:?}", output);
-                        self.add_fact(output.clone());
+                // Premises nothing derives are leaves, like known statements
+                let cost = premises.iter().try_fold(1, |total, premise| {
+                    if self.known.contains(premise) || !derived.contains(premise) {
+                        Some(total)
+                    } else {
+                        best.get(premise).map(|(cost, _, _)| total + cost)
                     }
-                    // Add the final proven statement as a fact
-                    println!("Adding final fact: {:?}", proof.0);
-                    self.add_fact(proof.0.clone());
-                    all_proofs.push(proof);
-                    proved_something = true;
-                } else {
-                    println!("Could not prove target {} yet", i);
-                    // Couldn't prove it yet, keep it for next round
-                    new_remaining.push(target.clone());
+                });
+                let Some(cost) = cost else {
+                    continue;
+                };
+                // On a tie, a native operation replaces the engine's own step, so
+                // an equality whose values are known is flipped by EqualFromEntries
+                let native = |op_code: u8| Operation::from_code(op_code).is_some_and(Operation::is_native);
+                match best.get(stmt) {
+                    Some((current, current_op, _)) if *current < cost || (*current == cost && (native(*current_op) || !native(*operation))) => {},
+                    _ => {
+                        best.insert(stmt.clone(), (cost, *operation, premises.clone()));
+                        changed = true;
+                    },
                 }
             }
+            if !changed {
+                return best;
+            }
+        }
+    }
+
+    // Materialise the proof DAG of `statement` from the chosen derivations
+    fn build_graph(&self, statement: &HashableStatement, derivations: &Derivations) -> ProofGraph {
+        let mut graph = ProofGraph::default();
+        self.add_step(statement, derivations, &mut graph, &mut HashMap::new());
+        graph
+    }
 
-            // If we didn't prove anything new this round, we're stuck
-            if !proved_something {
-                println!("\nNo new proofs found this round, stopping");
-                // These statements are unprovable with current knowledge
-                break;
+    // Add a statement's step after the steps for its premises, reusing the step
+    // of any statement already in the graph
+    fn add_step(
+        &self,
+        statement: &HashableStatement,
+        derivations: &Derivations,
+        graph: &mut ProofGraph,
+        ids: &mut HashMap<HashableStatement, StepId>,
+    ) -> StepId {
+        if let Some(id) = ids.get(statement) {
+            return *id;
+        }
+        let (operation, premises) = match derivations.get(statement) {
+            // Known statements are copied in from their source pods
+            _ if self.known.contains(statement) => (NativeOperation::CopyStatement as u8, vec![]),
+            // Literal operands are entries the proof creates itself
+            _ if is_self_entry(statement) => (NativeOperation::NewEntry as u8, vec![]),
+            Some((_, operation, premises)) => {
+                let premises = premises.iter()
+                    .map(|premise| self.add_step(premise, derivations, graph, ids))
+                    .collect();
+                (*operation, premises)
+            },
+            None => (NativeOperation::None as u8, vec![]),
+        };
+        let id = graph.steps.len();
+        graph.steps.push(ProofStep { operation, premises, statement: statement.clone() });
+        ids.insert(statement.clone(), id);
+        id
+    }
+
+    // The chain proving `statement` by `operation` from `premises`, with the
+    // cheapest proofs of the premises. None if a premise's proof goes through
+    // `statement` itself, or relies on a premise nothing establishes.
+    fn chain_via(
+        &self,
+        statement: &HashableStatement,
+        operation: u8,
+        premises: &[HashableStatement],
+        derivations: &Derivations,
+    ) -> Option<DeductionChain> {
+        let mut graph = ProofGraph::default();
+        let mut ids = HashMap::new();
+        let premises = premises.iter()
+            .map(|premise| self.add_step(premise, derivations, &mut graph, &mut ids))
+            .collect();
+        if ids.contains_key(statement) {
+            return None;
+        }
+        graph.steps.push(ProofStep { operation, premises, statement: statement.clone() });
+        flatten(statement, &graph)
+    }
+
+    // Package a can_prove result for the API, resolving the target's bindings
+    fn to_proof(&self, target: &WildcardStatement, statement: &HashableStatement, derivations: &Derivations) -> Option<Proof> {
+        Some(Proof {
+            statement: statement.clone(),
+            chain: self.chain_for(statement, derivations)?,
+            bindings: target.bindings(statement).unwrap_or_default(),
+        })
+    }
+
+    // The chain proving `statement`, or None if it relies on a premise nothing
+    // establishes
+    fn chain_for(&self, statement: &HashableStatement, derivations: &Derivations) -> Option<DeductionChain> {
+        flatten(statement, &self.build_graph(statement, derivations))
+    }
+
+    // Every statement proven for `target`, with its bindings
+    fn query_candidates(&self, target: &WildcardStatement, derivations: &Derivations) -> QueryCandidates {
+        self.prog.can_prove().into_iter()
+            .filter(|(proved_target, _)| proved_target == target)
+            .filter_map(|(_, stmt)| {
+                let bindings = target.bindings(stmt)?;
+                Some((stmt.clone(), self.chain_for(stmt, derivations)?, bindings))
+            })
+            .collect()
+    }
+
+    // Print a human-readable proof chain
+    pub fn print_proof(&self, statement: HashableStatement, chain: DeductionChain) {
+        println!("\nProved: {}", statement);
+        println!("\nProof steps:");
+        for (step, (op_code, inputs, output)) in chain.iter().enumerate() {
+            println!("\nStep {}:", step + 1);
+            // Known statements enter a chain as copies
+            if *op_code == NativeOperation::CopyStatement as u8 {
+                println!("Copied from source pod:");
+                println!("  => {}", output);
+                continue;
             }
+            println!("Operation: {}", step_name(*op_code));
+            println!("From:");
+            for input in inputs {
+                println!("  - {}", input);
+            }
+            println!("Deduced:");
+            println!("  => {}", output);
+        }
+    }
+
+    // Prove several targets in a single run. Proven targets feed back into the
+    // fact base inside the same fixpoint, so targets that depend on each other
+    // are resolved without re-running the program. Returns the shortest proof
+    // of each target, in target order, or the reason the first unproven target
+    // has no proof. Like a query's statements, the targets are only targets for
+    // this call.
+    #[instrument(level = "debug", skip_all, fields(targets = targets.len()))]
+    pub fn prove_multiple(&mut self, targets: Vec<WildcardStatement>) -> Result<Vec<Proof>, ProverError> {
+        for target in &targets {
+            self.demand(target);
+        }
+        self.run();
+        self.extract(|engine| {
+            engine.check_containers(&targets)?;
+            engine.best_proofs(&targets)
+                .into_iter()
+                .zip(&targets)
+                .map(|(proof, target)| proof.ok_or_else(|| engine.diagnose(target)))
+                .collect()
+        })
+    }
 
-            remaining_targets = new_remaining;
+    // Like `prove_multiple`, but stop at the first limit of `budget` that is hit
+    // and return the targets proven so far. Like `prove_bounded`, only a failed
+    // container lookup is an error.
+    #[instrument(level = "debug", skip_all, fields(targets = targets.len()))]
+    pub fn prove_multiple_bounded(&mut self, targets: Vec<WildcardStatement>, budget: &Budget) -> Result<Bounded<Vec<Proof>>, ProverError> {
+        for target in &targets {
+            self.demand(target);
         }
+        let limit = self.run_within(budget);
+        let mut proofs: Vec<Proof> = self.extract(|engine| {
+            engine.check_containers(&targets).map(|()| engine.best_proofs(&targets).into_iter().flatten().collect())
+        })?;
+        let limit = limit.or(drop_long_chains(budget, &mut proofs));
+        Ok(Bounded::new(limit, proofs))
+    }
 
-        println!("\nFinal proofs:");
-        for (i, proof) in all_proofs.iter().enumerate() {
-            println!("Proof {}: {:?}", i, proof.0);
+    // The shortest proof of each target, in target order, or None for a target
+    // that was not proven
+    fn best_proofs(&self, targets: &[WildcardStatement]) -> Vec<Option<Proof>> {
+        let derivations = self.cheapest_derivations();
+        let mut all_proofs = Vec::new();
+        for (i, target) in targets.iter().enumerate() {
+            let _target = debug_span!("target", index = i).entered();
+            let proof = self.prog.can_prove().into_iter()
+                .filter(|(proved_target, _)| proved_target == target)
+                .min_by_key(|(_, stmt)| proof_cost(&derivations, stmt));
+            match proof {
+                Some((_, stmt)) => {
+                    debug!(statement = %stmt, "proved target");
+                    all_proofs.push(self.to_proof(target, stmt, &derivations));
+                },
+                None => {
+                    debug!("could not prove target");
+                    all_proofs.push(None);
+                },
+            }
         }
         all_proofs
     }
 }
 
-ascent! {
+// The rules, shared by the sequential and parallel programs
+ascent_source! { prover_rules:
     // Core relations that track our knowledge and goals
     relation known_statement(HashableStatement);  // Statements we know to be true
-    relation target_statement(WildcardStatement);  // The statement we're trying to prove
-    relation can_prove(HashableStatement, DeductionChain);  // Statements we can prove with their proof chains
-    relation known_value(AnchoredKey, HashableValue);  // Values we know for specific keys
-    relation known_equal(AnchoredKey, AnchoredKey);  // Known equality relationships
-    relation known_gt(AnchoredKey, AnchoredKey);  // Known greater-than relationships
-    relation known_lt(AnchoredKey, AnchoredKey);  // Known less-than relationships
-    relation known_neq(AnchoredKey, AnchoredKey);  // Known not-equal relationships
-    relation known_contains(AnchoredKey, AnchoredKey);  // Known contains relationships
-    relation reachable_equal(AnchoredKey, AnchoredKey, DeductionChain);  // Equality relationships we can prove through chains
-    relation connected_to_target(AnchoredKey, AnchoredKey, DeductionChain);  // Chains that connect to our target statement
-
-    // Base case: directly match known statements with wildcard targets
-    can_prove(stmt, chain) <--
+    relation target_statement(WildcardStatement);  // The statements we're trying to prove
+    relation can_prove(WildcardStatement, HashableStatement);  // Statements we can prove, tagged with the target they satisfy
+    relation fact(HashableStatement);  // Known and proven statements
+    relation derivation(HashableStatement, u8, Vec<HashableStatement>);  // Single deduction steps: a statement, the operation deriving it and its premises
+    relation interned_key(AnchoredKey, KeyId);  // Ids of the concrete keys targets mention
+    relation known_value(KeyId, ValueId);  // Values we know for specific keys
+    relation value_by_key(String, KeyId, ValueId);  // Known values indexed by key name
+    relation value_by_origin(Origin, KeyId, ValueId);  // Known values indexed by origin
+    relation key_name(String);  // Distinct key names with known values
+    relation prefix_length(usize);  // Lengths of the key prefixes targets ask for
+    relation name_prefix(String, String);  // Key names indexed by their prefixes of those lengths
+    relation target_operand(WildcardAnchoredKey);  // Wildcard operands appearing in targets
+    relation match_counter(MatchCounter);  // The program's own counter, which rules match wildcard keys through
+    relation operand_value(WildcardAnchoredKey, KeyId, ValueId);  // Known values whose keys a target operand matches
+    relation known_equal(KeyId, KeyId);  // Known equality relationships
+    relation known_gt(KeyId, KeyId);  // Known greater-than relationships
+    relation known_lt(KeyId, KeyId);  // Known less-than relationships
+    relation known_neq(KeyId, KeyId);  // Known not-equal relationships
+    relation known_contains(KeyId, KeyId);  // Known contains relationships
+    relation known_not_contains(KeyId, KeyId);  // Known not-contains relationships
+    relation exhaustive_mode();  // Present when the closure should cover every key, not just demanded ones
+    relation equality_root(KeyId);  // Keys whose equalities a target asks about
+    relation demanded_key(KeyId);  // Keys that can reach an equality root
+    relation equal_edge(KeyId, KeyId);  // Single-hop equalities
+    lattice reachable_equal(KeyId, KeyId, Dual<usize>);  // Fewest hops from a key to an equality root
+    relation equal_chain(KeyId, KeyId);  // Equalities provable along shortest paths
+    relation connected_to_target(WildcardStatement, KeyId, KeyId);  // Key pairs that satisfy each target statement
+    relation container_error(WildcardStatement, KeyId, String);  // Containers whose membership lookup failed, by the target that looked
+
+    // Keys and values are interned by the engine on the way in (see `add_fact` and
+    // `add_target`), so joins compare ids rather than strings and commitments.
+    //
+    // Rules never build chains. Each deduction records a single `derivation` step
+    // whose premises are statements, so shared sub-proofs are stored once; the
+    // engine picks the cheapest derivations and materialises proofs afterwards.
+
+    // Everything we know: input facts, and proven targets, which become facts for
+    // the targets that depend on them
+    fact(stmt) <--
+        known_statement(stmt);
+
+    fact(stmt) <--
+        can_prove(_, stmt);
+
+    // Base case: directly match known or already-proven statements with wildcard targets
+    can_prove(wild_stmt, stmt) <--
         target_statement(wild_stmt),
-        known_statement(known_stmt),
-        if let WildcardStatement::Equal(wild_key, concrete_key) = wild_stmt,
-        if let HashableStatement::Equal(known_key, known_concrete) = known_stmt,
-        if wild_key.matches(&known_key) && concrete_key == known_concrete,
-        let stmt = known_stmt.clone(),
-        let chain = vec![];
+        if let WildcardStatement::Equal(wild_key, other_key) = wild_stmt,
+        known_equal(known_key, known_concrete),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &known_key, &known_concrete),
+        let stmt = HashableStatement::Equal(known_key.get().clone(), known_concrete.get().clone());
 
     // Prove equality through chains of known equalities
-    can_prove(stmt, chain) <--
+    can_prove(target_stmt, stmt) <--
         target_statement(target_stmt),
-        if let WildcardStatement::Equal(wild_key, concrete_key) = target_stmt,
-        connected_to_target(found_key, _, chain),
-        if wild_key.matches(&found_key),
-        let stmt = HashableStatement::Equal(found_key.clone(), concrete_key.clone());
+        if let WildcardStatement::Equal(_, _) = target_stmt,
+        connected_to_target(target_stmt, found_key, match_key),
+        let stmt = HashableStatement::Equal(found_key.get().clone(), match_key.get().clone());
 
     // Prove greater-than relationships through chains
-    can_prove(stmt, chain) <--
+    can_prove(target_stmt, stmt) <--
         target_statement(target_stmt),
-        if let WildcardStatement::Gt(wild_key, concrete_key) = target_stmt,
-        connected_to_target(found_key, _, chain),
-        if wild_key.matches(&found_key),
-        let stmt = HashableStatement::Gt(found_key.clone(), concrete_key.clone());
+        if let WildcardStatement::Gt(_, _) = target_stmt,
+        connected_to_target(target_stmt, found_key, match_key),
+        let stmt = HashableStatement::Gt(found_key.get().clone(), match_key.get().clone());
 
     // Prove less-than relationships through chains
-    can_prove(stmt, chain) <--
+    can_prove(target_stmt, stmt) <--
         target_statement(target_stmt),
-        if let WildcardStatement::Lt(wild_key, concrete_key) = target_stmt,
-        connected_to_target(found_key, _, chain),
-        if wild_key.matches(&found_key),
-        let stmt = HashableStatement::Lt(found_key.clone(), concrete_key.clone());
+        if let WildcardStatement::Lt(_, _) = target_stmt,
+        connected_to_target(target_stmt, found_key, match_key),
+        let stmt = HashableStatement::Lt(found_key.get().clone(), match_key.get().clone());
 
     // Prove not-equal relationships through chains
-    can_prove(stmt, chain) <--
+    can_prove(target_stmt, stmt) <--
         target_statement(target_stmt),
-        if let WildcardStatement::NotEqual(wild_key, concrete_key) = target_stmt,
-        connected_to_target(found_key, _, chain),
-        if wild_key.matches(&found_key),
-        let stmt = HashableStatement::NotEqual(found_key.clone(), concrete_key.clone());
+        if let WildcardStatement::NotEqual(_, _) = target_stmt,
+        connected_to_target(target_stmt, found_key, match_key),
+        let stmt = HashableStatement::NotEqual(found_key.get().clone(), match_key.get().clone());
 
     // Prove contains relationships through chains
-    can_prove(stmt, chain) <--
+    can_prove(target_stmt, stmt) <--
         target_statement(target_stmt),
-        if let WildcardStatement::Contains(wild_key, concrete_key) = target_stmt,
-        connected_to_target(found_key, _, chain),
-        if wild_key.matches(&found_key),
-        let stmt = HashableStatement::Contains(found_key.clone(), concrete_key.clone());
-
-    // Extract value assignments from known statements
-    known_value(ak, v) <--
-        known_statement(stmt),
-        if let HashableStatement::ValueOf(ak, v) = stmt;
-
-    // Extract equality relationships from known statements
-    known_equal(ak1, ak2) <--
-        known_statement(stmt),
-        if let HashableStatement::Equal(ak1, ak2) = stmt;
-
-    // Extract greater-than relationships from known statements
-    known_gt(ak1, ak2) <--
-        known_statement(stmt),
-        if let HashableStatement::Gt(ak1, ak2) = stmt;
-
-    // Extract less-than relationships from known statements
-    known_lt(ak1, ak2) <--
-        known_statement(stmt),
-        if let HashableStatement::Lt(ak1, ak2) = stmt;
-
-    // Extract not-equal relationships from known statements
-    known_neq(ak1, ak2) <--
-        known_statement(stmt),
-        if let HashableStatement::NotEqual(ak1, ak2) = stmt;
-
-    // Extract contains relationships from known statements
-    known_contains(ak1, ak2) <--
-        known_statement(stmt),
-        if let HashableStatement::Contains(ak1, ak2) = stmt;
-
-    // Base case: directly known equalities are reachable with empty chain
-    reachable_equal(x, y, chain) <--
-        known_equal(x, y),
-        let chain = vec![];
+        if let WildcardStatement::Contains(_, _) = target_stmt,
+        connected_to_target(target_stmt, found_key, match_key),
+        let stmt = HashableStatement::Contains(found_key.get().clone(), match_key.get().clone());
 
-    // Also add the reverse direction for known equalities (equality is symmetric)
-    reachable_equal(y, x, chain) <--
-        known_equal(x, y),
-        let chain = vec![];
-
-    // Add equality from values to reachable_equal
-    reachable_equal(x, y, chain) <--
-        known_value(x, v1),
-        known_value(y, v2),
-        if v1 == v2,
-        let chain = vec![(
-            NativeOperation::EqualFromEntries as u8,
-            vec![
-                HashableStatement::ValueOf(x.clone(), v1.clone()),
-                HashableStatement::ValueOf(y.clone(), v2.clone())
-            ],
-            HashableStatement::Equal(x.clone(), y.clone())
-        )];
-
-    // Build chains of equalities through transitivity (if a=b and b=c, then a=c)
-    reachable_equal(x, z, chain) <--
-        reachable_equal(x, y, chain1),
-        known_equal(y, z),
-        let chain = {
-            let mut chain = chain1.clone();
-            chain.push((
-                NativeOperation::TransitiveEqualFromStatements as u8,
-                vec![
-                    HashableStatement::Equal(x.clone(), y.clone()),
-                    HashableStatement::Equal(y.clone(), z.clone())
-                ],
-                HashableStatement::Equal(x.clone(), z.clone())
-            ));
-            chain
+    // Prove not-contains relationships through chains
+    can_prove(target_stmt, stmt) <--
+        target_statement(target_stmt),
+        if let WildcardStatement::NotContains(_, _) = target_stmt,
+        connected_to_target(target_stmt, found_key, match_key),
+        let stmt = HashableStatement::NotContains(found_key.get().clone(), match_key.get().clone());
+
+    // Prove ValueOf targets with a known entry whose value fits the pattern; the
+    // entry is copied in when the proof is materialised
+    can_prove(target_stmt, stmt) <--
+        target_statement(target_stmt),
+        if let WildcardStatement::ValueOf(wild_key, pattern) = target_stmt,
+        operand_value(wild_key, found_key, v),
+        if pattern.matches(v),
+        let stmt = HashableStatement::ValueOf(found_key.get().clone(), v.get().clone());
+
+    // Prove sums from entry values (overflowing sums are not provable)
+    can_prove(target_stmt, stmt),
+    derivation(stmt, NativeOperation::SumOf as u8, premises) <--
+        target_statement(target_stmt),
+        if let WildcardStatement::SumOf(wild_key, concrete_key1, concrete_key2) = target_stmt,
+        operand_value(wild_key, found_key, v1),
+        interned_key(concrete_key1, key1),
+        interned_key(concrete_key2, key2),
+        known_value(key1, v2),
+        known_value(key2, v3),
+        if let HashableValue::Int(i1) = v1.get(),
+        if let HashableValue::Int(i2) = v2.get(),
+        if let HashableValue::Int(i3) = v3.get(),
+        if i2.checked_add(*i3) == Some(*i1),
+        let stmt = HashableStatement::SumOf(found_key.get().clone(), concrete_key1.clone(), concrete_key2.clone()),
+        let premises = vec![
+            HashableStatement::ValueOf(found_key.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(concrete_key1.clone(), v2.get().clone()),
+            HashableStatement::ValueOf(concrete_key2.clone(), v3.get().clone())
+        ];
+
+    // Prove products from entry values (overflowing products are not provable)
+    can_prove(target_stmt, stmt),
+    derivation(stmt, NativeOperation::ProductOf as u8, premises) <--
+        target_statement(target_stmt),
+        if let WildcardStatement::ProductOf(wild_key, concrete_key1, concrete_key2) = target_stmt,
+        operand_value(wild_key, found_key, v1),
+        interned_key(concrete_key1, key1),
+        interned_key(concrete_key2, key2),
+        known_value(key1, v2),
+        known_value(key2, v3),
+        if let HashableValue::Int(i1) = v1.get(),
+        if let HashableValue::Int(i2) = v2.get(),
+        if let HashableValue::Int(i3) = v3.get(),
+        if i2.checked_mul(*i3) == Some(*i1),
+        let stmt = HashableStatement::ProductOf(found_key.get().clone(), concrete_key1.clone(), concrete_key2.clone()),
+        let premises = vec![
+            HashableStatement::ValueOf(found_key.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(concrete_key1.clone(), v2.get().clone()),
+            HashableStatement::ValueOf(concrete_key2.clone(), v3.get().clone())
+        ];
+
+    // Prove maximums from entry values
+    can_prove(target_stmt, stmt),
+    derivation(stmt, NativeOperation::MaxOf as u8, premises) <--
+        target_statement(target_stmt),
+        if let WildcardStatement::MaxOf(wild_key, concrete_key1, concrete_key2) = target_stmt,
+        operand_value(wild_key, found_key, v1),
+        interned_key(concrete_key1, key1),
+        interned_key(concrete_key2, key2),
+        known_value(key1, v2),
+        known_value(key2, v3),
+        if let HashableValue::Int(i1) = v1.get(),
+        if let HashableValue::Int(i2) = v2.get(),
+        if let HashableValue::Int(i3) = v3.get(),
+        if i1 == i2.max(i3),
+        let stmt = HashableStatement::MaxOf(found_key.get().clone(), concrete_key1.clone(), concrete_key2.clone()),
+        let premises = vec![
+            HashableStatement::ValueOf(found_key.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(concrete_key1.clone(), v2.get().clone()),
+            HashableStatement::ValueOf(concrete_key2.clone(), v3.get().clone())
+        ];
+
+    // Existing arithmetic statements prove themselves
+    can_prove(target_stmt, stmt) <--
+        target_statement(target_stmt),
+        fact(known_stmt),
+        match_counter(counter),
+        if let Some(stmt) = match (target_stmt, known_stmt) {
+            (WildcardStatement::SumOf(w, c1, c2), HashableStatement::SumOf(k, k1, k2))
+            | (WildcardStatement::ProductOf(w, c1, c2), HashableStatement::ProductOf(k, k1, k2))
+            | (WildcardStatement::MaxOf(w, c1, c2), HashableStatement::MaxOf(k, k1, k2))
+                if counter.matches(w, k) && c1 == k1 && c2 == k2 => Some(known_stmt.clone()),
+            _ => None,
         };
 
+    // Resolve target operands to the values they can bind. Operands with an exact
+    // key name look up that name, a key pattern on a concrete origin looks up the
+    // origin's values, and a prefix looks up the key names starting with it. Only
+    // other patterns on a wildcard origin scan every value.
+    value_by_key(ak.get().1.clone(), ak, v) <--
+        known_value(ak, v);
+
+    value_by_origin(ak.get().0.clone(), ak, v) <--
+        known_value(ak, v);
+
+    key_name(name) <--
+        value_by_key(name, _, _);
+
+    target_operand(w) <--
+        target_statement(stmt),
+        for w in stmt.operands();
+
+    prefix_length(prefix.len()) <--
+        target_operand(w),
+        if let KeyPattern::Prefix(prefix) = &w.1;
+
+    name_prefix(name[..*len].to_string(), name) <--
+        prefix_length(len),
+        key_name(name),
+        if name.is_char_boundary(*len);
+
+    operand_value(w, k, v) <--
+        target_operand(w),
+        if let Some(name) = w.1.exact(),
+        value_by_key(name, k, v),
+        match_counter(counter),
+        if counter.matches(w, &k);
+
+    operand_value(w, k, v) <--
+        target_operand(w),
+        if w.1.exact().is_none(),
+        if let WildcardId::Concrete(origin) = w.0.clone(),
+        value_by_origin(origin, k, v),
+        match_counter(counter),
+        if counter.matches(w, &k);
+
+    operand_value(w, k, v) <--
+        target_operand(w),
+        if !matches!(w.0, WildcardId::Concrete(_)),
+        if let KeyPattern::Prefix(prefix) = w.1.clone(),
+        name_prefix(prefix, name),
+        value_by_key(name, k, v),
+        match_counter(counter),
+        if counter.matches(w, &k);
+
+    operand_value(w, k, v) <--
+        target_operand(w),
+        if !matches!(w.0, WildcardId::Concrete(_)),
+        if matches!(w.1, KeyPattern::Named(_) | KeyPattern::Glob(_)),
+        known_value(k, v),
+        match_counter(counter),
+        if counter.matches(w, &k);
+
+    // Proven relationships feed back as facts for the targets that depend on
+    // them; known ones are added by `add_fact`
+    known_equal(x, y) <--
+        connected_to_target(stmt, x, y),
+        if let WildcardStatement::Equal(..) = stmt;
+
+    known_gt(x, y) <--
+        connected_to_target(stmt, x, y),
+        if let WildcardStatement::Gt(..) = stmt;
+
+    known_lt(x, y) <--
+        connected_to_target(stmt, x, y),
+        if let WildcardStatement::Lt(..) = stmt;
+
+    known_neq(x, y) <--
+        connected_to_target(stmt, x, y),
+        if let WildcardStatement::NotEqual(..) = stmt;
+
+    known_contains(x, y) <--
+        connected_to_target(stmt, x, y),
+        if let WildcardStatement::Contains(..) = stmt;
+
+    known_not_contains(x, y) <--
+        connected_to_target(stmt, x, y),
+        if let WildcardStatement::NotContains(..) = stmt;
+
+    // Equality is proven in two phases so that cycles in the fact base cannot
+    // produce ever-longer chains: `reachable_equal` only tracks the fewest hops
+    // from a key to an equality root, and `equal_chain` then rebuilds proofs
+    // along paths of exactly that length.
+    //
+    // The closure is computed backwards from `equality_root`s (magic-set style):
+    // only keys that can reach a root are demanded, and only edges into demanded
+    // keys are materialised. In exhaustive mode every key is a root.
+
+    // In exhaustive mode every key is a root
+    equality_root(k) <--
+        exhaustive_mode(),
+        known_equal(k, _);
+
+    equality_root(k) <--
+        exhaustive_mode(),
+        known_equal(_, k);
+
+    equality_root(k) <--
+        exhaustive_mode(),
+        known_value(k, _);
+
+    // Otherwise only the right-hand keys of equality targets are roots: the key
+    // itself when it is concrete, or every key the wildcard can bind to
+    equality_root(k) <--
+        target_statement(stmt),
+        if let WildcardStatement::Equal(_, other_key) = stmt,
+        if let Some(key) = other_key.as_concrete(),
+        interned_key(key, k);
+
+    equality_root(k) <--
+        target_statement(stmt),
+        if let WildcardStatement::Equal(_, other_key) = stmt,
+        if other_key.as_concrete().is_none(),
+        operand_value(other_key, k, _);
+
+    equality_root(k) <--
+        target_statement(stmt),
+        if let WildcardStatement::Equal(_, other_key) = stmt,
+        if other_key.as_concrete().is_none(),
+        known_equal(k, _),
+        match_counter(counter),
+        if counter.matches(other_key, &k);
+
+    equality_root(k) <--
+        target_statement(stmt),
+        if let WildcardStatement::Equal(_, other_key) = stmt,
+        if other_key.as_concrete().is_none(),
+        known_equal(_, k),
+        match_counter(counter),
+        if counter.matches(other_key, &k);
+
+    // Keys that can reach a demanded key through a single equality hop are demanded too
+    demanded_key(k) <--
+        equality_root(k);
+
+    demanded_key(x) <--
+        demanded_key(y),
+        known_equal(x, y);
+
+    demanded_key(x) <--
+        demanded_key(y),
+        known_equal(y, x);
+
+    demanded_key(x) <--
+        demanded_key(y),
+        known_value(y, v),
+        known_value(x, v);
+
+    // Known and proven equalities are single hops
+    equal_edge(x, y) <--
+        demanded_key(y),
+        known_equal(x, y);
+
+    // Also add the reverse direction for known equalities (equality is symmetric).
+    // The flip is a step of its own, so proofs copy in the stored orientation.
+    // It is not a native operation, so extraction prefers EqualFromEntries
+    // whenever both values are known.
+    equal_edge(y, x),
+    derivation(HashableStatement::Equal(y.get().clone(), x.get().clone()), Operation::SymmetricEqual.code(), premises) <--
+        demanded_key(x),
+        known_equal(x, y),
+        let premises = vec![HashableStatement::Equal(x.get().clone(), y.get().clone())];
+
+    // Two distinct keys holding the same value are a single hop via EqualFromEntries
+    equal_edge(x, y),
+    derivation(HashableStatement::Equal(x.get().clone(), y.get().clone()), NativeOperation::EqualFromEntries as u8, premises) <--
+        demanded_key(y),
+        known_value(y, v),
+        known_value(x, v),
+        if x != y,
+        let premises = vec![
+            HashableStatement::ValueOf(x.get().clone(), v.get().clone()),
+            HashableStatement::ValueOf(y.get().clone(), v.get().clone())
+        ];
+
+    // Reachability: every edge into a root is one hop
+    reachable_equal(x, z, Dual(1)) <--
+        equality_root(z),
+        equal_edge(x, z);
+
+    // Reachability through transitivity (if a=b and b=c, then a=c); the lattice
+    // keeps only the shortest distance, so this terminates on cyclic fact sets
+    reachable_equal(x, z, Dual(d + 1)) <--
+        equal_edge(x, y),
+        reachable_equal(y, z, ?Dual(d)),
+        if x != z;
+
+    // Proof reconstruction: single-hop proofs come straight from the edges
+    equal_chain(x, z) <--
+        equal_edge(x, z),
+        reachable_equal(x, z, ?Dual(d)),
+        if *d == 1;
+
+    // Extend shortest proofs one edge at a time, only along paths whose length
+    // matches the shortest distance
+    equal_chain(x, z),
+    derivation(HashableStatement::Equal(x.get().clone(), z.get().clone()), NativeOperation::TransitiveEqualFromStatements as u8, premises) <--
+        equal_chain(y, z),
+        reachable_equal(y, z, ?Dual(d1)),
+        equal_edge(x, y),
+        reachable_equal(x, z, ?Dual(d2)),
+        if x != z && *d2 == d1 + 1,
+        let premises = vec![
+            HashableStatement::Equal(x.get().clone(), y.get().clone()),
+            HashableStatement::Equal(y.get().clone(), z.get().clone())
+        ];
+
     // Find chains that connect to our target key for equality statements
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y) <--
         target_statement(stmt),
-        if let WildcardStatement::Equal(wild_key, concrete_key) = stmt,
-        reachable_equal(x, y, chain),
-        if y == concrete_key;
+        if let WildcardStatement::Equal(wild_key, other_key) = stmt,
+        equality_root(y),
+        match_counter(counter),
+        if counter.matches(other_key, &y),
+        equal_chain(x, y),
+        if counter.matches_pair(wild_key, other_key, &x, &y);
 
     // Prove equality from values (if two keys have the same value, they're equal)
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::Equal(x.get().clone(), y.get().clone()), NativeOperation::EqualFromEntries as u8, premises) <--
         target_statement(stmt),
-        if let WildcardStatement::Equal(wild_key, concrete_key) = stmt,
-        known_value(found_key, v1),
-        known_value(match_key, v2),
-        if wild_key.matches(&found_key) && match_key == concrete_key,
-        if v1 == v2,
+        if let WildcardStatement::Equal(wild_key, other_key) = stmt,
+        operand_value(wild_key, found_key, v1),
+        operand_value(other_key, match_key, v2),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        if to_value(v1.get()) == to_value(v2.get()),
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![(
-            NativeOperation::EqualFromEntries as u8,
-            vec![
-                HashableStatement::ValueOf(x.clone(), v1.clone()),
-                HashableStatement::ValueOf(y.clone(), v2.clone())
-            ],
-            HashableStatement::Equal(x.clone(), y.clone())
-        )];
+        let premises = vec![
+            HashableStatement::ValueOf(x.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(y.get().clone(), v2.get().clone())
+        ];
 
     // Find chains for greater-than relationships:
     // 1. Direct value comparisons (e.g., 10 > 5)
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::Gt(x.get().clone(), y.get().clone()), NativeOperation::GtFromEntries as u8, premises) <--
         target_statement(stmt),
-        if let WildcardStatement::Gt(wild_key, concrete_key) = stmt,
-        known_value(found_key, v1),
-        known_value(match_key, v2),
-        if wild_key.matches(&found_key) && match_key == concrete_key,
-        if let HashableValue::Int(i1) = v1,
-        if let HashableValue::Int(i2) = v2,
+        if let WildcardStatement::Gt(wild_key, other_key) = stmt,
+        operand_value(wild_key, found_key, v1),
+        operand_value(other_key, match_key, v2),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        if let HashableValue::Int(i1) = v1.get(),
+        if let HashableValue::Int(i2) = v2.get(),
         if i1 > i2,
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![(
-            NativeOperation::GtFromEntries as u8,
-            vec![
-                HashableStatement::ValueOf(x.clone(), v1.clone()),
-                HashableStatement::ValueOf(y.clone(), v2.clone())
-            ],
-            HashableStatement::Gt(x.clone(), y.clone())
-        )];
+        let premises = vec![
+            HashableStatement::ValueOf(x.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(y.get().clone(), v2.get().clone())
+        ];
 
     // 2. Existing greater-than statements
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y) <--
         target_statement(stmt),
-        if let WildcardStatement::Gt(wild_key, concrete_key) = stmt,
-        known_statement(known_stmt),
-        if let HashableStatement::Gt(found_key, match_key) = known_stmt,
-        if wild_key.matches(&found_key) && match_key == concrete_key,
+        if let WildcardStatement::Gt(wild_key, other_key) = stmt,
+        known_gt(found_key, match_key),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
         let x = found_key.clone(),
-        let y = match_key.clone(),
-        let chain = vec![];
+        let y = match_key.clone();
 
     // Find chains for less-than relationships:
     // 1. Direct value comparisons (e.g., 5 < 10)
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::Lt(x.get().clone(), y.get().clone()), NativeOperation::LtFromEntries as u8, premises) <--
         target_statement(stmt),
-        if let WildcardStatement::Lt(wild_key, concrete_key) = stmt,
-        known_value(found_key, v1),
-        known_value(match_key, v2),
-        if wild_key.matches(&found_key) && match_key == concrete_key,
-        if let HashableValue::Int(i1) = v1,
-        if let HashableValue::Int(i2) = v2,
+        if let WildcardStatement::Lt(wild_key, other_key) = stmt,
+        operand_value(wild_key, found_key, v1),
+        operand_value(other_key, match_key, v2),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        if let HashableValue::Int(i1) = v1.get(),
+        if let HashableValue::Int(i2) = v2.get(),
         if i1 < i2,
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![(
-            NativeOperation::LtFromEntries as u8,
-            vec![
-                HashableStatement::ValueOf(x.clone(), v1.clone()),
-                HashableStatement::ValueOf(y.clone(), v2.clone())
-            ],
-            HashableStatement::Lt(x.clone(), y.clone())
-        )];
+        let premises = vec![
+            HashableStatement::ValueOf(x.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(y.get().clone(), v2.get().clone())
+        ];
 
     // 2. Existing less-than statements
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y) <--
         target_statement(stmt),
-        if let WildcardStatement::Lt(wild_key, concrete_key) = stmt,
-        known_statement(known_stmt),
-        if let HashableStatement::Lt(found_key, match_key) = known_stmt,
-        if wild_key.matches(&found_key) && match_key == concrete_key,
+        if let WildcardStatement::Lt(wild_key, other_key) = stmt,
+        known_lt(found_key, match_key),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
         let x = found_key.clone(),
-        let y = match_key.clone(),
-        let chain = vec![];
+        let y = match_key.clone();
 
     // Find chains for not-equal relationships:
     // 1. Converting greater-than to not-equal (if a > b, then a ≠ b)
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::NotEqual(x.get().clone(), y.get().clone()), NativeOperation::GtToNotEqual as u8, premises) <--
         target_statement(stmt),
-        if let WildcardStatement::NotEqual(wild_key, concrete_key) = stmt,
-        known_statement(known_stmt),
-        if let HashableStatement::Gt(found_key, match_key) = known_stmt,
-        if wild_key.matches(&found_key) && match_key == concrete_key,
+        if let WildcardStatement::NotEqual(wild_key, other_key) = stmt,
+        known_gt(found_key, match_key),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![(
-            NativeOperation::GtToNotEqual as u8,
-            vec![HashableStatement::Gt(x.clone(), y.clone())],
-            HashableStatement::NotEqual(x.clone(), y.clone())
-        )];
+        let premises = vec![HashableStatement::Gt(x.get().clone(), y.get().clone())];
 
     // 2. Converting less-than to not-equal (if a < b, then a ≠ b)
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::NotEqual(x.get().clone(), y.get().clone()), NativeOperation::LtToNotEqual as u8, premises) <--
         target_statement(stmt),
-        if let WildcardStatement::NotEqual(wild_key, concrete_key) = stmt,
-        known_statement(known_stmt),
-        if let HashableStatement::Lt(found_key, match_key) = known_stmt,
-        if wild_key.matches(&found_key) && match_key == concrete_key,
+        if let WildcardStatement::NotEqual(wild_key, other_key) = stmt,
+        known_lt(found_key, match_key),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![(
-            NativeOperation::LtToNotEqual as u8,
-            vec![HashableStatement::Lt(x.clone(), y.clone())],
-            HashableStatement::NotEqual(x.clone(), y.clone())
-        )];
-
-    // 3. Existing not-equal statements
-    connected_to_target(x, y, chain) <--
+        let premises = vec![HashableStatement::Lt(x.get().clone(), y.get().clone())];
+
+    // 3. Comparing entry values directly (if two keys hold different values, they're not equal)
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::NotEqual(x.get().clone(), y.get().clone()), NativeOperation::NotEqualFromEntries as u8, premises) <--
         target_statement(stmt),
-        if let WildcardStatement::NotEqual(wild_key, concrete_key) = stmt,
-        known_statement(known_stmt),
-        if let HashableStatement::NotEqual(found_key, match_key) = known_stmt,
-        if wild_key.matches(&found_key) && match_key == concrete_key,
+        if let WildcardStatement::NotEqual(wild_key, other_key) = stmt,
+        operand_value(wild_key, found_key, v1),
+        operand_value(other_key, match_key, v2),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        if to_value(v1.get()) != to_value(v2.get()),
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![];
+        let premises = vec![
+            HashableStatement::ValueOf(x.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(y.get().clone(), v2.get().clone())
+        ];
+
+    // 4. Existing not-equal statements
+    connected_to_target(stmt, x, y) <--
+        target_statement(stmt),
+        if let WildcardStatement::NotEqual(wild_key, other_key) = stmt,
+        known_neq(found_key, match_key),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        let x = found_key.clone(),
+        let y = match_key.clone();
 
     // Find chains for contains relationships:
     // 1. Direct value comparisons (checking if a value is in an array or set)
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::Contains(x.get().clone(), y.get().clone()), NativeOperation::ContainsFromEntries as u8, premises) <--
         target_statement(stmt),
-        if let WildcardStatement::Contains(wild_key, concrete_key) = stmt,
-        known_value(found_key, v1),
-        known_value(match_key, v2),
-        if wild_key.matches(&found_key) && match_key == concrete_key,
-        if check_contains(&v1, &v2),
+        if let WildcardStatement::Contains(wild_key, other_key) = stmt,
+        operand_value(wild_key, found_key, v1),
+        operand_value(other_key, match_key, v2),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        if let Ok(true) = check_contains(&v1, &v2),
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![(
-            NativeOperation::ContainsFromEntries as u8,
-            vec![
-                HashableStatement::ValueOf(x.clone(), v1.clone()),
-                HashableStatement::ValueOf(y.clone(), v2.clone())
-            ],
-            HashableStatement::Contains(x.clone(), y.clone())
-        )];
+        let premises = vec![
+            HashableStatement::ValueOf(x.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(y.get().clone(), v2.get().clone())
+        ];
 
     // 2. Existing contains statements
-    connected_to_target(x, y, chain) <--
+    connected_to_target(stmt, x, y) <--
         target_statement(stmt),
-        if let WildcardStatement::Contains(wild_key, concrete_key) = stmt,
-        known_statement(known_stmt),
-        if let HashableStatement::Contains(found_key, match_key) = known_stmt,
-        if wild_key.matches(&found_key) && match_key == concrete_key,
+        if let WildcardStatement::Contains(wild_key, other_key) = stmt,
+        known_contains(found_key, match_key),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        let x = found_key.clone(),
+        let y = match_key.clone();
+
+    // Find chains for not-contains relationships:
+    // 1. Direct value comparisons (checking that a value is absent from an array or set)
+    connected_to_target(stmt, x, y),
+    derivation(HashableStatement::NotContains(x.get().clone(), y.get().clone()), NativeOperation::NotContainsFromEntries as u8, premises) <--
+        target_statement(stmt),
+        if let WildcardStatement::NotContains(wild_key, other_key) = stmt,
+        operand_value(wild_key, found_key, v1),
+        operand_value(other_key, match_key, v2),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        if let Ok(true) = check_not_contains(&v1, &v2),
         let x = found_key.clone(),
         let y = match_key.clone(),
-        let chain = vec![];
+        let premises = vec![
+            HashableStatement::ValueOf(x.get().clone(), v1.get().clone()),
+            HashableStatement::ValueOf(y.get().clone(), v2.get().clone())
+        ];
+
+    // 2. Existing not-contains statements
+    connected_to_target(stmt, x, y) <--
+        target_statement(stmt),
+        if let WildcardStatement::NotContains(wild_key, other_key) = stmt,
+        known_not_contains(found_key, match_key),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        let x = found_key.clone(),
+        let y = match_key.clone();
+
+    // Lookups that failed while checking containment, which the engine reports
+    // rather than treating as absence
+    container_error(stmt, found_key, error) <--
+        target_statement(stmt),
+        if let Some((wild_key, other_key)) = match stmt {
+            WildcardStatement::Contains(wild_key, other_key)
+            | WildcardStatement::NotContains(wild_key, other_key) => Some((wild_key, other_key)),
+            _ => None,
+        },
+        operand_value(wild_key, found_key, v1),
+        operand_value(other_key, match_key, v2),
+        match_counter(counter),
+        if counter.matches_pair(wild_key, other_key, &found_key, &match_key),
+        if let Err(error) = check_contains(&v1, &v2);
+}
+
+ascent! {
+    pub struct AscentProgram;
+    include_source!(prover_rules);
+}
+
+#[cfg(feature = "parallel")]
+ascent_par! {
+    pub struct ParAscentProgram;
+    include_source!(prover_rules);
 }
@@ -0,0 +1,102 @@
+use crate::types::HashableValue;
+use pod2::frontend::AnchoredKey;
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::Arc;
+
+// An interned key or value: a compact id plus a shared pointer to the data.
+// Hashing and equality only look at the id, so joins never rehash key strings
+// or container commitments. Ids are only comparable within one Interner.
+pub struct Interned<T> {
+    id: u32,
+    value: Arc<T>,
+}
+
+pub type KeyId = Interned<AnchoredKey>;
+pub type ValueId = Interned<HashableValue>;
+
+impl<T> Interned<T> {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> Clone for Interned<T> {
+    fn clone(&self) -> Self {
+        Self { id: self.id, value: Arc::clone(&self.value) }
+    }
+}
+
+impl<T> PartialEq for Interned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Interned<T> {}
+
+impl<T> Hash for Interned<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> Deref for Interned<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Interned<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}:{:?}", self.id, self.value)
+    }
+}
+
+// Hands out one id per distinct key and value. Each is hashed once, here, on
+// its way into the engine.
+#[derive(Default)]
+pub struct Interner {
+    keys: HashMap<AnchoredKey, KeyId>,
+    values: HashMap<HashableValue, ValueId>,
+}
+
+impl Interner {
+    pub fn key(&mut self, key: &AnchoredKey) -> KeyId {
+        intern(&mut self.keys, key)
+    }
+
+    pub fn value(&mut self, value: &HashableValue) -> ValueId {
+        intern(&mut self.values, value)
+    }
+
+    pub fn key_pair(&mut self, key1: &AnchoredKey, key2: &AnchoredKey) -> (KeyId, KeyId) {
+        (self.key(key1), self.key(key2))
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn value_count(&self) -> usize {
+        self.values.len()
+    }
+}
+
+fn intern<T: Clone + Eq + Hash>(table: &mut HashMap<T, Interned<T>>, item: &T) -> Interned<T> {
+    if let Some(interned) = table.get(item) {
+        return interned.clone();
+    }
+    let interned = Interned { id: table.len() as u32, value: Arc::new(item.clone()) };
+    table.insert(item.clone(), interned.clone());
+    interned
+}
@@ -1,3 +1,6 @@
 pub mod types;
 pub mod engine;
+pub mod verify;
+pub mod minimise;
+mod intern;
 mod tests;
\ No newline at end of file
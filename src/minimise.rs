@@ -0,0 +1,51 @@
+use crate::types::{DeductionChain, HashableStatement, Proof, QueryProof};
+use std::collections::HashSet;
+
+// A chain with its redundant steps pruned, and how many were removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinimisedChain {
+    pub chain: DeductionChain,
+    pub removed: usize,
+}
+
+// Prune `chain` down to the steps the `proven` statements depend on. A step
+// that repeats an earlier step's output is dropped, then a backwards pass from
+// the proven statements keeps only steps whose outputs are used. Kept steps stay
+// in their original order, so every input is still established before it is used.
+pub fn minimise_chain(chain: &DeductionChain, proven: &[HashableStatement]) -> MinimisedChain {
+    let mut seen = HashSet::new();
+    let unique: Vec<_> = chain.iter().filter(|(_, _, output)| seen.insert(output)).collect();
+
+    let mut needed: HashSet<HashableStatement> = proven.iter().cloned().collect();
+    let mut kept = Vec::new();
+    for step in unique.into_iter().rev() {
+        let (_, inputs, output) = step;
+        if needed.contains(output) {
+            needed.extend(inputs.iter().cloned());
+            kept.push(step.clone());
+        }
+    }
+    kept.reverse();
+
+    MinimisedChain { removed: chain.len() - kept.len(), chain: kept }
+}
+
+impl Proof {
+    // Prune the chain down to the steps the statement depends on, returning how
+    // many steps were removed
+    pub fn minimise(&mut self) -> usize {
+        let minimised = minimise_chain(&self.chain, std::slice::from_ref(&self.statement));
+        self.chain = minimised.chain;
+        minimised.removed
+    }
+}
+
+impl QueryProof {
+    // Prune the combined chain down to the steps the query's statements depend
+    // on, returning how many steps were removed
+    pub fn minimise(&mut self) -> usize {
+        let minimised = minimise_chain(&self.chain, &self.statements);
+        self.chain = minimised.chain;
+        minimised.removed
+    }
+}
@@ -1,8 +1,13 @@
 #[cfg(test)]
 mod tests {
-    use pod2::{frontend::{AnchoredKey, Origin, PodClass}, middleware::{containers::Array as MiddlewareArray, hash_str, NativeOperation, PodId, Value as MiddlewareValue}};
+    use pod2::{frontend::{AnchoredKey, Origin, PodClass}, middleware::{containers::{Array as MiddlewareArray, Dictionary as MiddlewareDictionary, Set as MiddlewareSet}, hash_str, NativeOperation, PodId, Value as MiddlewareValue}};
 
-    use crate::{engine::DeductionEngine, types::{HashableStatement, HashableValue, WildcardAnchoredKey, WildcardId, WildcardStatement}};
+    use std::collections::{HashMap, HashSet};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    use crate::{engine::{DeductionEngine, EvaluationMode}, intern::Interner, minimise::minimise_chain, verify::{verify_chain, verify_engine_chain, ChainError}, types::{self_origin, Binding, BindingKind, Bound, Bounded, Budget, CancellationToken, ConjunctiveQuery, DeductionChain, HashableStatement, HashableValue, KeyPattern, Limit, Operation, Proof, ProverError, RangeQuery, ValuePattern, WildcardAnchoredKey, WildcardId, WildcardStatement}};
 
     fn make_signed_origin(id: &str) -> Origin {
         Origin(PodClass::Signed, PodId(hash_str(id)))
@@ -12,6 +17,65 @@ mod tests {
         AnchoredKey(make_signed_origin(id), key.to_string())
     }
 
+    // The steps of a chain that deduce something, without the steps copying in
+    // known statements or introducing literal entries
+    fn deduction_steps(chain: &DeductionChain) -> DeductionChain {
+        chain.iter()
+            .filter(|(op_code, _, _)| *op_code != NativeOperation::CopyStatement as u8 && *op_code != NativeOperation::NewEntry as u8)
+            .cloned()
+            .collect()
+    }
+
+    // What each proof establishes, with its bindings and chain length, in a
+    // stable order. Ties between equally short chains can be broken differently
+    // depending on the order rows are derived in, so runs are compared by this
+    // rather than by their chains.
+    fn summarise(proofs: &[Proof]) -> Vec<(String, Vec<String>, usize)> {
+        let mut summary: Vec<_> = proofs.iter()
+            .map(|proof| {
+                let mut bindings: Vec<String> = proof.bindings.iter()
+                    .map(|(name, binding)| format!("{}: {:?}", name, binding))
+                    .collect();
+                bindings.sort();
+                (proof.statement.to_string(), bindings, proof.chain.len())
+            })
+            .collect();
+        summary.sort();
+        summary
+    }
+
+    // A tracing subscriber that records the names of the spans opened and counts
+    // the events emitted while it is installed
+    #[derive(Clone, Default)]
+    struct TraceRecorder {
+        spans: Arc<Mutex<Vec<String>>>,
+        events: Arc<AtomicUsize>,
+    }
+
+    impl tracing::Subscriber for TraceRecorder {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            let mut spans = self.spans.lock().unwrap();
+            spans.push(span.metadata().name().to_string());
+            tracing::span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, _: &tracing::Event<'_>) {
+            self.events.fetch_add(1, Ordering::Relaxed);
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
     #[test]
     fn test_transitive_equality() {
 
@@ -21,40 +85,41 @@ mod tests {
         engine.add_fact(HashableStatement::Equal(
             make_anchored_key("X", "X"),
             make_anchored_key("Y", "Y"),
-        ));
+        )).unwrap();
 
         engine.add_fact(HashableStatement::Equal(
             make_anchored_key("Y", "Y"),
             make_anchored_key("Z", "Z"),
-        ));
+        )).unwrap();
 
         engine.add_fact(HashableStatement::Equal(
             make_anchored_key("Z", "Z"),
             make_anchored_key("Q", "Q"),
-        ));
+        )).unwrap();
 
         engine.add_fact(HashableStatement::Equal(
             make_anchored_key("Q", "Q"),
             make_anchored_key("W", "W"),
-        ));
+        )).unwrap();
 
         // Try to prove X = W
         engine.set_target(WildcardStatement::Equal(
-            WildcardAnchoredKey(WildcardId::Named("X".to_string()), "X".to_string()),
-            make_anchored_key("W", "W"),
+            WildcardAnchoredKey::wildcard("X".to_string(), "X"),
+            make_anchored_key("W", "W").into(),
         ));
 
-        let proofs = engine.prove();
+        let proofs = engine.prove().unwrap();
 
         assert!(!proofs.is_empty(), "Should be able to prove X = W");
 
  
         
         // Check that we used transitive equality
-        let (stmt, chain) = &proofs[0];
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
         engine.print_proof(stmt.clone(), chain.clone());
-        assert_eq!(chain.len(), 3, "Should have exactly three deduction steps");
-        let (op_code, inputs, _) = &chain[0];
+        let steps = deduction_steps(chain);
+        assert_eq!(steps.len(), 3, "Should have exactly three deduction steps");
+        let (op_code, inputs, _) = &steps[0];
         assert_eq!(
             *op_code,
             NativeOperation::TransitiveEqualFromStatements as u8,
@@ -63,6 +128,630 @@ mod tests {
         assert_eq!(inputs.len(), 2, "Should use exactly three input statements");
     }
 
+    #[test]
+    fn test_cyclic_equality_terminates() {
+        let mut engine = DeductionEngine::new();
+
+        // a = b, b = a, b = c, c = a: every pair is connected through a cycle
+        engine.add_fact(HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("b", "v"))).unwrap();
+        engine.add_fact(HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("a", "v"))).unwrap();
+        engine.add_fact(HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("c", "v"))).unwrap();
+        engine.add_fact(HashableStatement::Equal(make_anchored_key("c", "v"), make_anchored_key("a", "v"))).unwrap();
+        engine.add_fact(HashableStatement::Equal(make_anchored_key("c", "v"), make_anchored_key("d", "v"))).unwrap();
+
+        engine.set_target(WildcardStatement::Equal(
+            WildcardAnchoredKey::concrete(make_signed_origin("a"), "v".to_string()),
+            make_anchored_key("d", "v").into(),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        assert!(!proofs.is_empty(), "Should prove a = d through the cycle");
+        for Proof { statement: stmt, chain, .. } in &proofs {
+            engine.print_proof(stmt.clone(), chain.clone());
+            // a -> c -> d is the shortest path: a = c comes from flipping c = a (or
+            // from a = b, b = c), then one transitive step reaches d
+            let steps = deduction_steps(chain);
+            assert_eq!(steps.len(), 2, "Should only keep the shortest chain");
+            assert_eq!(steps[1].0, NativeOperation::TransitiveEqualFromStatements as u8);
+        }
+    }
+
+    #[test]
+    fn test_reversed_equality_is_copied_then_flipped() {
+        let mut engine = DeductionEngine::new();
+        let stored = HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("a", "v"));
+        engine.add_fact(stored.clone()).unwrap();
+        engine.set_target(WildcardStatement::Equal(
+            WildcardAnchoredKey::concrete(make_signed_origin("a"), "v".to_string()),
+            make_anchored_key("b", "v").into(),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        let flipped = HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("b", "v"));
+        // The stored orientation is copied in, then flipped by an explicit step
+        let expected: DeductionChain = vec![
+            (NativeOperation::CopyStatement as u8, vec![stored.clone()], stored.clone()),
+            (Operation::SymmetricEqual.code(), vec![stored], flipped.clone()),
+        ];
+        assert_eq!(proofs[0].statement, flipped);
+        assert_eq!(proofs[0].chain, expected);
+        assert!(!Operation::from_code(expected[1].0).unwrap().is_native(), "The flip is the engine's own step");
+
+        // With both values known, the equality is re-derived natively instead
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("a", "v"), HashableValue::Int(3))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("b", "v"), HashableValue::Int(3))).unwrap();
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        let steps = deduction_steps(&proofs[0].chain);
+        assert_eq!(steps.len(), 1);
+        assert_eq!(steps[0].0, NativeOperation::EqualFromEntries as u8);
+        assert!(proofs[0].chain.iter().all(|(op_code, _, _)| Operation::from_code(*op_code).is_some_and(Operation::is_native)));
+    }
+
+    #[test]
+    fn test_dense_equality_clique() {
+        let mut engine = DeductionEngine::new();
+
+        // Every key equals every other key, both as statements and by value
+        let names: Vec<String> = (0..8).map(|i| format!("k{}", i)).collect();
+        for a in &names {
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key(a, "v"), HashableValue::Int(7))).unwrap();
+            for b in &names {
+                if a != b {
+                    engine.add_fact(HashableStatement::Equal(make_anchored_key(a, "v"), make_anchored_key(b, "v"))).unwrap();
+                }
+            }
+        }
+
+        engine.set_target(WildcardStatement::Equal(
+            WildcardAnchoredKey::wildcard("v".to_string(), "n"),
+            make_anchored_key("k0", "v").into(),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        let mut proved: Vec<_> = proofs.iter().map(|proof| proof.statement.clone()).collect();
+        proved.dedup();
+        for name in names.iter().skip(1) {
+            let expected = HashableStatement::Equal(make_anchored_key(name, "v"), make_anchored_key("k0", "v"));
+            assert!(proved.contains(&expected), "Should prove {} = k0", name);
+        }
+        for Proof { chain, .. } in &proofs {
+            assert!(deduction_steps(chain).len() <= 1, "Every key is one hop away from k0");
+        }
+    }
+
+    #[test]
+    fn test_demand_driven_explores_less() {
+        // 200 disjoint equality chains of 5 keys each; only one is relevant to the target
+        let build = |mode: EvaluationMode| {
+            let mut engine = DeductionEngine::with_mode(mode);
+            for c in 0..200 {
+                for i in 0..4 {
+                    engine.add_fact(HashableStatement::Equal(
+                        make_anchored_key(&format!("c{}_{}", c, i), "v"),
+                        make_anchored_key(&format!("c{}_{}", c, i + 1), "v"),
+                    )).unwrap();
+                }
+            }
+            engine.set_target(WildcardStatement::Equal(
+                WildcardAnchoredKey::concrete(make_signed_origin("c7_0"), "v".to_string()),
+                make_anchored_key("c7_4", "v").into(),
+            ));
+            engine
+        };
+
+        let mut exhaustive = build(EvaluationMode::Exhaustive);
+        let start = Instant::now();
+        let exhaustive_proofs = exhaustive.prove().unwrap();
+        let exhaustive_time = start.elapsed();
+
+        let mut demand = build(EvaluationMode::DemandDriven);
+        let start = Instant::now();
+        let demand_proofs = demand.prove().unwrap();
+        let demand_time = start.elapsed();
+
+        let closure_size = |engine: &DeductionEngine| {
+            engine.stats().relation("equal_edge") + engine.stats().relation("reachable_equal")
+        };
+        let exhaustive_work = closure_size(&exhaustive);
+        let demand_work = closure_size(&demand);
+        println!(
+            "exhaustive: {} tuples in {:?}, demand-driven: {} tuples in {:?}",
+            exhaustive_work, exhaustive_time, demand_work, demand_time
+        );
+
+        assert_eq!(exhaustive_proofs, demand_proofs, "Both modes must find the same proofs");
+        assert!(!demand_proofs.is_empty(), "Should prove c7_0 = c7_4");
+        assert!(
+            demand_work * 50 < exhaustive_work,
+            "Demand-driven evaluation should only touch the relevant chain"
+        );
+    }
+
+    #[test]
+    fn test_wildcard_right_operand_in_both_modes() {
+        // B:w only ever appears on the right of a known equality and holds no value
+        for mode in [EvaluationMode::Exhaustive, EvaluationMode::DemandDriven] {
+            let mut engine = DeductionEngine::with_mode(mode);
+            engine.add_fact(HashableStatement::Equal(make_anchored_key("A", "v"), make_anchored_key("C", "x"))).unwrap();
+            engine.add_fact(HashableStatement::Equal(make_anchored_key("C", "x"), make_anchored_key("B", "w"))).unwrap();
+            engine.set_target(WildcardStatement::Equal(
+                WildcardAnchoredKey::wildcard("v".to_string(), "p"),
+                WildcardAnchoredKey::wildcard("w".to_string(), "q"),
+            ));
+            let proofs = engine.prove().unwrap();
+            let expected = HashableStatement::Equal(make_anchored_key("A", "v"), make_anchored_key("B", "w"));
+            assert!(proofs.iter().any(|proof| proof.statement == expected), "{:?} should prove A:v = B:w", mode);
+        }
+    }
+
+    #[test]
+    fn test_proof_graph() {
+        let mut engine = DeductionEngine::new();
+        for (a, b) in [("a", "b"), ("b", "c"), ("c", "d"), ("d", "e")] {
+            engine.add_fact(HashableStatement::Equal(make_anchored_key(a, "v"), make_anchored_key(b, "v"))).unwrap();
+        }
+        engine.set_target(WildcardStatement::Equal(
+            WildcardAnchoredKey::concrete(make_signed_origin("a"), "v".to_string()),
+            make_anchored_key("e", "v").into(),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        let root = HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("e", "v"));
+        let graph = engine.proof_graph(&root).expect("a = e was proven");
+        assert_eq!(graph.root(), Some(&root));
+
+        // Each statement appears once, after the steps establishing its premises
+        for (id, step) in graph.steps.iter().enumerate() {
+            assert!(step.premises.iter().all(|premise| *premise < id));
+            assert_eq!(graph.steps.iter().filter(|other| other.statement == step.statement).count(), 1);
+        }
+
+        // Four copied facts and three transitive steps, flattened to the usual chain
+        assert_eq!(graph.steps.len(), 7);
+        assert_eq!(graph.to_chain(), Ok(proofs[0].chain.clone()));
+        assert_eq!(deduction_steps(&proofs[0].chain).len(), 3);
+
+        let unproven = HashableStatement::Equal(make_anchored_key("e", "v"), make_anchored_key("z", "v"));
+        assert!(engine.proof_graph(&unproven).is_none());
+    }
+
+    #[test]
+    fn test_interning_large_fact_set() {
+        // 2000 pods whose tags are one of twenty arrays and whose scores repeat
+        let arrays: Vec<HashableValue> = (0..20i64)
+            .map(|i| HashableValue::Array(MiddlewareArray::new(&vec![MiddlewareValue::from(i), MiddlewareValue::from(i + 1)]).unwrap()))
+            .collect();
+        let mut engine = DeductionEngine::with_mode(EvaluationMode::DemandDriven);
+        for i in 0..2000usize {
+            let pod = format!("pod{}", i);
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key(&pod, "tags"), arrays[i % 20].clone())).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key(&pod, "score"), HashableValue::Int((i % 50) as i64))).unwrap();
+        }
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("ref", "tags"), arrays[0].clone())).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("ref", "threshold"), HashableValue::Int(47))).unwrap();
+        engine.add_target(WildcardStatement::Equal(
+            WildcardAnchoredKey::wildcard("tags".to_string(), "n"),
+            make_anchored_key("ref", "tags").into(),
+        ));
+        engine.add_target(WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("score".to_string(), "n"),
+            make_anchored_key("ref", "threshold").into(),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        let stats = engine.stats();
+        println!("proved {} statements over 4002 entries in {:?}", proofs.len(), stats.evaluation_time + stats.extraction_time);
+
+        // 100 pods share ref's tags (as does ref itself) and 80 pods score 48 or 49
+        let equal = proofs.iter().filter(|proof| matches!(proof.statement, HashableStatement::Equal(..))).count();
+        let gt = proofs.iter().filter(|proof| matches!(proof.statement, HashableStatement::Gt(..))).count();
+        assert_eq!(gt, 80);
+        assert!(equal >= 100);
+
+        // Every key is interned once, and the relations only ever see 70 distinct values
+        assert_eq!(stats.relation("known_value"), 4002);
+        assert_eq!(stats.interned_keys, 4002);
+        assert_eq!(stats.interned_values, 70);
+
+        // Joins hash ids where they used to hash whole values
+        let mut interner = Interner::default();
+        let values: Vec<HashableValue> = (0..2000).map(|i| arrays[i % 20].clone()).collect();
+        let ids: Vec<_> = values.iter().map(|value| interner.value(value)).collect();
+        let start = Instant::now();
+        for _ in 0..20 {
+            assert_eq!(values.iter().collect::<HashSet<_>>().len(), 20);
+        }
+        let value_time = start.elapsed();
+        let start = Instant::now();
+        for _ in 0..20 {
+            assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 20);
+        }
+        let id_time = start.elapsed();
+        println!(
+            "hashing values: {:?}, hashing ids: {:?} ({:.1}x faster)",
+            value_time, id_time, value_time.as_secs_f64() / id_time.as_secs_f64().max(1e-9)
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_backend_matches_sequential() {
+        let build = |backend: crate::engine::Backend| {
+            let mut engine = DeductionEngine::with_backend(EvaluationMode::DemandDriven, backend);
+            for i in 0..50 {
+                engine.add_fact(HashableStatement::ValueOf(
+                    make_anchored_key(&format!("pod{}", i), "score"),
+                    HashableValue::Int(i % 7),
+                )).unwrap();
+                engine.add_fact(HashableStatement::Equal(
+                    make_anchored_key(&format!("pod{}", i), "link"),
+                    make_anchored_key(&format!("pod{}", i + 1), "link"),
+                )).unwrap();
+            }
+            engine.add_target(WildcardStatement::Gt(
+                WildcardAnchoredKey::wildcard("score".to_string(), "a".to_string()),
+                make_anchored_key("pod3", "score").into(),
+            ));
+            engine.add_target(WildcardStatement::Equal(
+                WildcardAnchoredKey::concrete(make_signed_origin("pod0"), "link".to_string()),
+                make_anchored_key("pod20", "link").into(),
+            ));
+            engine
+        };
+
+        let sequential = summarise(&build(crate::engine::Backend::Sequential).prove_best().unwrap());
+        let parallel = summarise(&build(crate::engine::Backend::Parallel).prove_best().unwrap());
+        assert!(!sequential.is_empty(), "Should prove both targets");
+        assert_eq!(sequential, parallel, "Both backends must find the same proofs");
+    }
+
+    #[test]
+    fn test_bounded_proving() {
+        let build = || {
+            let mut engine = DeductionEngine::new();
+            for i in 0..10 {
+                engine.add_fact(HashableStatement::Equal(
+                    make_anchored_key(&format!("pod{}", i), "v"),
+                    make_anchored_key(&format!("pod{}", i + 1), "v"),
+                )).unwrap();
+            }
+            engine.set_target(WildcardStatement::Equal(
+                WildcardAnchoredKey::concrete(make_signed_origin("pod0"), "v".to_string()),
+                make_anchored_key("pod10", "v").into(),
+            ));
+            engine
+        };
+
+        // Without limits a bounded call matches an unbounded one
+        let proofs = build().prove().unwrap();
+        assert!(!proofs.is_empty(), "Should prove pod0 = pod10");
+        assert_eq!(build().prove_bounded(&Budget::default()), Ok(Bounded::Complete(proofs.clone())));
+
+        // Each limit is reported as the reason the run stopped
+        let cancellation = CancellationToken::default();
+        cancellation.cancel();
+        let cancelled = build().prove_bounded(&Budget { cancellation: Some(cancellation), ..Budget::default() }).unwrap();
+        assert_eq!(cancelled, Bounded::BudgetExceeded { limit: Limit::Cancelled, partial: vec![] });
+
+        let expired = build().prove_bounded(&Budget { deadline: Some(Instant::now()), ..Budget::default() }).unwrap();
+        assert_eq!(expired.exceeded(), Some(Limit::Deadline));
+
+        let tuples = build().prove_bounded(&Budget { max_tuples: Some(5), ..Budget::default() }).unwrap();
+        assert_eq!(tuples.exceeded(), Some(Limit::Tuples));
+
+        let rounds = build().prove_bounded(&Budget { max_rounds: Some(0), ..Budget::default() }).unwrap();
+        assert_eq!(rounds.exceeded(), Some(Limit::Rounds));
+
+        // The chain limit applies to the finished proofs
+        let longest = proofs.iter().map(|proof| proof.chain.len()).max().unwrap();
+        let short = build().prove_bounded(&Budget { max_chain_length: Some(longest - 1), ..Budget::default() }).unwrap();
+        assert_eq!(short.exceeded(), Some(Limit::ChainLength));
+        assert!(short.value().iter().all(|proof| proof.chain.len() < longest));
+    }
+
+    #[test]
+    fn test_bounded_run_resumes() {
+        let build = || {
+            let mut engine = DeductionEngine::new();
+            for i in 0..200 {
+                engine.add_fact(HashableStatement::Equal(
+                    make_anchored_key(&format!("pod{}", i), "v"),
+                    make_anchored_key(&format!("pod{}", i + 1), "v"),
+                )).unwrap();
+            }
+            engine.set_target(WildcardStatement::Equal(
+                WildcardAnchoredKey::wildcard("v".to_string(), "n"),
+                make_anchored_key("pod200", "v").into(),
+            ));
+            engine
+        };
+        let fresh = build().prove().unwrap();
+        assert!(fresh.len() >= 200, "Every pod is equal to pod200");
+
+        // Stop after the first slice, part way through the closure, then carry on
+        // without a budget from wherever the run stopped
+        let mut engine = build();
+        let partial = engine.prove_bounded(&Budget { max_rounds: Some(0), ..Budget::default() }).unwrap();
+        assert_eq!(partial.exceeded(), Some(Limit::Rounds));
+        assert!(partial.value().len() <= fresh.len());
+        assert_eq!(summarise(&engine.prove().unwrap()), summarise(&fresh));
+    }
+
+    #[test]
+    fn test_bounded_prove_multiple() {
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("a", "value"), HashableValue::Int(10))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("b", "value"), HashableValue::Int(5))).unwrap();
+        let targets = vec![WildcardStatement::Gt(
+            WildcardAnchoredKey::concrete(make_signed_origin("a"), "value".to_string()),
+            make_anchored_key("b", "value").into(),
+        )];
+
+        let budget = Budget::timeout(std::time::Duration::from_secs(60));
+        let proofs = engine.prove_multiple_bounded(targets, &budget).unwrap();
+        assert_eq!(proofs.exceeded(), None);
+        assert_eq!(proofs.into_inner().len(), 1, "Should prove a > b well within the deadline");
+    }
+
+    #[test]
+    fn test_ill_typed_facts_are_rejected() {
+        let name = HashableStatement::ValueOf(make_anchored_key("A", "name"), HashableValue::String("alice".to_string()));
+        let gt = HashableStatement::Gt(make_anchored_key("A", "name"), make_anchored_key("B", "age"));
+
+        // A comparison over a key already known to hold a string
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(name.clone()).unwrap();
+        assert!(matches!(engine.add_fact(gt.clone()), Err(ProverError::IllTypedFact { fact, .. }) if fact == gt));
+
+        // A string value for a key a known comparison needs to be an integer
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(gt).unwrap();
+        assert!(matches!(engine.add_fact(name.clone()), Err(ProverError::IllTypedFact { fact, .. }) if fact == name));
+
+        // Containment needs a container
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(30))).unwrap();
+        let contains = HashableStatement::Contains(make_anchored_key("A", "age"), make_anchored_key("B", "age"));
+        assert!(matches!(engine.add_fact(contains), Err(ProverError::IllTypedFact { .. })));
+    }
+
+    #[test]
+    fn test_unproven_targets_are_explained() {
+        let name = HashableStatement::ValueOf(make_anchored_key("A", "name"), HashableValue::String("alice".to_string()));
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(name.clone()).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "age"), HashableValue::Int(3))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("C", "age"), HashableValue::Int(7))).unwrap();
+
+        // Comparing a string is a type error, pinned on the entry holding it
+        let over_string = WildcardStatement::Gt(make_anchored_key("A", "name").into(), make_anchored_key("B", "age").into());
+        engine.set_target(over_string.clone());
+        assert!(matches!(engine.prove(), Err(ProverError::IllTypedFact { fact, .. }) if fact == name));
+
+        // A well-typed target the facts don't establish has no proof
+        let backwards = WildcardStatement::Gt(make_anchored_key("B", "age").into(), make_anchored_key("C", "age").into());
+        let forwards = WildcardStatement::Gt(make_anchored_key("C", "age").into(), make_anchored_key("B", "age").into());
+        let result = engine.prove_multiple(vec![forwards, backwards.clone()]);
+        assert_eq!(result, Err(ProverError::NoProof(backwards)));
+        // The engine's own target is still the one set above
+        assert!(matches!(engine.prove(), Err(ProverError::IllTypedFact { fact, .. }) if fact == name));
+    }
+
+    #[test]
+    fn test_container_targets_are_checked() {
+        let mut engine = DeductionEngine::new();
+        let one = HashableStatement::ValueOf(make_anchored_key("A", "value"), HashableValue::Int(1));
+        engine.add_fact(one).unwrap();
+
+        // Containment in a dictionary is not something the rules prove
+        let dict = MiddlewareDictionary::new(&HashMap::from([(hash_str("one"), MiddlewareValue::from(1i64))])).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("D", "entries"), HashableValue::Dictionary(dict))).unwrap();
+        let in_dict = WildcardStatement::Contains(make_anchored_key("D", "entries").into(), make_anchored_key("A", "value").into());
+        engine.set_target(in_dict.clone());
+        assert!(matches!(engine.prove(), Err(ProverError::UnsupportedTarget { target, .. }) if target == in_dict));
+
+        // Set membership is looked up, and a lookup that succeeds is a proof
+        let set = MiddlewareSet::new(&HashSet::from([MiddlewareValue::from(1i64), MiddlewareValue::from(2i64)])).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("S", "members"), HashableValue::Set(set))).unwrap();
+        engine.set_target(WildcardStatement::Contains(make_anchored_key("S", "members").into(), make_anchored_key("A", "value").into()));
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(deduction_steps(&proofs[0].chain)[0].0, NativeOperation::ContainsFromEntries as u8);
+    }
+
+    #[test]
+    fn test_tracing_spans() {
+        let recorder = TraceRecorder::default();
+        let proofs = tracing::subscriber::with_default(recorder.clone(), || {
+            let mut engine = DeductionEngine::new();
+            engine.add_fact(HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("b", "v"))).unwrap();
+            engine.add_fact(HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("c", "v"))).unwrap();
+            engine.prove_multiple(vec![WildcardStatement::Equal(
+                WildcardAnchoredKey::concrete(make_signed_origin("a"), "v".to_string()),
+                make_anchored_key("c", "v").into(),
+            )])
+        });
+        assert_eq!(proofs.unwrap().len(), 1);
+
+        let spans = recorder.spans.lock().unwrap();
+        for name in ["prove_multiple", "round", "target", "relaxation"] {
+            assert!(spans.iter().any(|span| span == name), "Expected a {} span, got {:?}", name, spans);
+        }
+        assert!(recorder.events.load(Ordering::Relaxed) > 0, "Rule matches should be reported as events");
+    }
+
+    #[test]
+    fn test_prove_stats() {
+        let setup = || {
+            let mut engine = DeductionEngine::new();
+            for i in 0..5 {
+                engine.add_fact(HashableStatement::ValueOf(make_anchored_key(&format!("pod{}", i), "score"), HashableValue::Int(i))).unwrap();
+            }
+            engine.set_target(WildcardStatement::Gt(
+                WildcardAnchoredKey::wildcard("score".to_string(), "n"),
+                make_anchored_key("pod2", "score").into(),
+            ));
+            engine
+        };
+        let mut engine = setup();
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 2, "pod3 and pod4 score higher than pod2");
+
+        let stats = engine.stats().clone();
+        println!("{}", stats);
+        assert_eq!(stats.relation("known_value"), 5);
+        assert_eq!(stats.relation("can_prove"), 2);
+        assert!(stats.relation("connected_to_target") >= 2);
+        assert_eq!(stats.relation("no_such_relation"), 0);
+        assert!(stats.rounds > 0);
+        assert!(stats.candidate_matches > 0, "Wildcard operands are matched against keys");
+
+        // Relation sizes are totals, so a second call with nothing new reports the same ones
+        engine.prove().unwrap();
+        assert_eq!(engine.stats().relation_sizes, stats.relation_sizes);
+        assert!(engine.stats().to_string().contains("can_prove: 2"));
+
+        // Matches are counted per engine: engines proving on other threads at the
+        // same time don't add to the count
+        let matches = move || {
+            let mut engine = setup();
+            engine.prove().unwrap();
+            engine.stats().candidate_matches
+        };
+        let busy: Vec<_> = (0..4).map(|_| std::thread::spawn(move || (0..20).map(|_| matches()).collect::<Vec<_>>())).collect();
+        for _ in 0..20 {
+            assert_eq!(matches(), stats.candidate_matches);
+        }
+        for thread in busy {
+            assert!(thread.join().unwrap().iter().all(|count| *count == stats.candidate_matches));
+        }
+    }
+
+    #[test]
+    fn test_engine_proofs_verify() {
+        let facts = vec![
+            HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("b", "v")),
+            HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("c", "v")),
+            HashableStatement::Equal(make_anchored_key("d", "v"), make_anchored_key("c", "v")),
+            HashableStatement::ValueOf(make_anchored_key("X", "score"), HashableValue::Int(10)),
+            HashableStatement::ValueOf(make_anchored_key("Y", "score"), HashableValue::Int(4)),
+            HashableStatement::ValueOf(make_anchored_key("Z", "score"), HashableValue::Int(6)),
+            HashableStatement::Gt(make_anchored_key("Y", "score"), make_anchored_key("W", "score")),
+        ];
+        let mut engine = DeductionEngine::new();
+        for fact in &facts {
+            engine.add_fact(fact.clone()).unwrap();
+        }
+        let score = || WildcardAnchoredKey::wildcard("score".to_string(), "n");
+        let targets = vec![
+            WildcardStatement::Equal(
+                WildcardAnchoredKey::concrete(make_signed_origin("a"), "v".to_string()),
+                make_anchored_key("d", "v").into(),
+            ),
+            WildcardStatement::Gt(score(), make_anchored_key("Y", "score").into()),
+            WildcardStatement::Lt(score(), WildcardAnchoredKey::literal(HashableValue::Int(5))),
+            WildcardStatement::NotEqual(score(), make_anchored_key("W", "score").into()),
+            WildcardStatement::SumOf(score(), make_anchored_key("Y", "score"), make_anchored_key("Z", "score")),
+        ];
+        for target in targets {
+            engine.add_target(target);
+        }
+
+        // Every proof the engine finds must stand up on its own
+        let proofs = engine.prove().unwrap();
+        assert!(proofs.len() >= 5);
+        for proof in &proofs {
+            // a = d flips d = c, which only verify_engine_chain accepts
+            assert_eq!(verify_engine_chain(&facts, &proof.chain, &proof.statement), Ok(()), "{:?}", proof);
+        }
+    }
+
+    #[test]
+    fn test_verify_chain_rejects_bad_steps() {
+        let x = HashableStatement::ValueOf(make_anchored_key("X", "score"), HashableValue::Int(10));
+        let y = HashableStatement::ValueOf(make_anchored_key("Y", "score"), HashableValue::Int(4));
+        let gt = HashableStatement::Gt(make_anchored_key("X", "score"), make_anchored_key("Y", "score"));
+        let lt = HashableStatement::Lt(make_anchored_key("X", "score"), make_anchored_key("Y", "score"));
+        let facts = vec![x.clone(), y.clone()];
+        let copy = |stmt: &HashableStatement| (NativeOperation::CopyStatement as u8, vec![stmt.clone()], stmt.clone());
+        let gt_step = (NativeOperation::GtFromEntries as u8, vec![x.clone(), y.clone()], gt.clone());
+
+        let chain = vec![copy(&x), copy(&y), gt_step.clone()];
+        assert_eq!(verify_chain(&facts, &chain, &gt), Ok(()));
+
+        // The values don't support the claim
+        let chain = vec![copy(&x), copy(&y), (NativeOperation::LtFromEntries as u8, vec![x.clone(), y.clone()], lt.clone())];
+        assert_eq!(verify_chain(&facts, &chain, &lt), Err(ChainError::UnsatisfiedStep { step: 2, operation: "LtFromEntries" }));
+
+        // Inputs must be earlier outputs, so facts are copied in before they are used
+        let chain = vec![copy(&x), gt_step.clone()];
+        assert_eq!(verify_chain(&facts, &chain, &gt), Err(ChainError::UnavailableInput { step: 1, input: y.clone() }));
+        assert_eq!(verify_chain(&facts, &[], &x), Err(ChainError::Unproven(x.clone())));
+
+        // Only facts can be copied
+        let z = HashableStatement::ValueOf(make_anchored_key("Z", "score"), HashableValue::Int(1));
+        assert_eq!(verify_chain(&facts, &[copy(&z)], &z), Err(ChainError::UnavailableInput { step: 0, input: z.clone() }));
+
+        // Equalities must be used the way round they were established
+        let a_b = HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("b", "v"));
+        let b_a = HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("a", "v"));
+        let facts = vec![a_b.clone()];
+        assert_eq!(verify_chain(&facts, &[copy(&a_b)], &b_a), Err(ChainError::Unproven(b_a.clone())));
+        let chain = vec![copy(&a_b), (Operation::SymmetricEqual.code(), vec![a_b.clone()], b_a.clone())];
+        assert_eq!(verify_chain(&facts, &chain, &b_a), Err(ChainError::NonNativeOperation { step: 1, operation: Operation::SymmetricEqual.name() }));
+        assert_eq!(verify_engine_chain(&facts, &chain, &b_a), Ok(()), "Engine steps are accepted only when asked for");
+        let facts = vec![x.clone(), y.clone()];
+
+        // Op codes must be known, and match the shape of the step
+        assert_eq!(verify_chain(&facts, &[(200, vec![], gt.clone())], &gt), Err(ChainError::UnknownOperation { step: 0, op_code: 200 }));
+        let chain = vec![(NativeOperation::SumOf as u8, vec![x.clone(), y.clone()], gt.clone())];
+        assert_eq!(verify_chain(&facts, &chain, &gt), Err(ChainError::MalformedStep { step: 0, operation: "SumOf" }));
+
+        // A valid chain must still establish the claim
+        let chain = vec![gt_step];
+        assert_eq!(verify_chain(&facts, &chain, &lt), Err(ChainError::Unproven(lt)));
+    }
+
+    #[test]
+    fn test_minimise_chain() {
+        let facts = vec![
+            HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("b", "v")),
+            HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("c", "v")),
+            HashableStatement::Equal(make_anchored_key("c", "v"), make_anchored_key("d", "v")),
+            HashableStatement::ValueOf(make_anchored_key("X", "score"), HashableValue::Int(10)),
+            HashableStatement::ValueOf(make_anchored_key("Y", "score"), HashableValue::Int(4)),
+        ];
+        let mut engine = DeductionEngine::new();
+        for fact in &facts {
+            engine.add_fact(fact.clone()).unwrap();
+        }
+        let a_d = WildcardStatement::Equal(
+            WildcardAnchoredKey::concrete(make_signed_origin("a"), "v".to_string()),
+            make_anchored_key("d", "v").into(),
+        );
+        let x_y = WildcardStatement::Gt(make_anchored_key("X", "score").into(), make_anchored_key("Y", "score").into());
+        let proofs = engine.prove_multiple(vec![a_d, x_y]).unwrap();
+
+        // Both chains run together, with a step repeated, as a combined proof would have them
+        let mut padded = proofs[1].chain.clone();
+        padded.extend(proofs[0].chain.iter().cloned());
+        padded.push(proofs[0].chain[0].clone());
+        let mut proof = Proof { chain: padded.clone(), ..proofs[0].clone() };
+
+        let removed = proof.minimise();
+        assert_eq!(removed, proofs[1].chain.len() + 1);
+        assert_eq!(proof.chain, proofs[0].chain, "The engine's own chain has nothing to prune");
+        assert_eq!(verify_chain(&facts, &proof.chain, &proof.statement), Ok(()));
+
+        // Pruning for both statements only drops the repeated step
+        let both = minimise_chain(&padded, &[proofs[0].statement.clone(), proofs[1].statement.clone()]);
+        assert_eq!(both.removed, 1);
+        assert_eq!(both.chain.len(), padded.len() - 1);
+    }
+
     #[test]
     fn test_wildcard_gt() {
         let mut engine = DeductionEngine::new();
@@ -71,35 +760,35 @@ mod tests {
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("X", "value"),
             HashableValue::Int(10),
-        ));
+        )).unwrap();
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("Y", "value"),
             HashableValue::Int(5),
-        ));
+        )).unwrap();
 
         // Add a direct GT statement
         engine.add_fact(HashableStatement::Gt(
             make_anchored_key("A", "value"),
             make_anchored_key("B", "value"),
-        ));
+        )).unwrap();
 
         // Test case 1: Find GT through value comparison
         let target = WildcardStatement::Gt(
-            WildcardAnchoredKey(WildcardId::Named("n".to_string()), "value".to_string()),
-            make_anchored_key("Y", "value"),
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
         );
         engine.set_target(target.clone());
 
-        let proofs = engine.prove();
+        let proofs = engine.prove().unwrap();
         assert!(!proofs.is_empty(), "Should find X > Y through value comparison");
-        let (stmt, chain) = &proofs[0];
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
         
         // Print the proof in a readable format
         engine.print_proof(stmt.clone(), chain.clone());
         
-        assert_eq!(chain.len(), 1, "Should use GtFromEntries");
+        assert_eq!(deduction_steps(chain).len(), 1, "Should use GtFromEntries");
         assert_eq!(
-            chain[0].0,
+            deduction_steps(chain)[0].0,
             NativeOperation::GtFromEntries as u8,
             "Should use GtFromEntries operation"
         );
@@ -114,6 +803,439 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_prove_best_picks_shortest_chain() {
+        let mut engine = DeductionEngine::new();
+
+        // X > Y is both directly known and derivable from the entry values
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("X", "value"), HashableValue::Int(10))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("Y", "value"), HashableValue::Int(5))).unwrap();
+        engine.add_fact(HashableStatement::Gt(make_anchored_key("X", "value"), make_anchored_key("Y", "value"))).unwrap();
+
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
+        ));
+
+        let all_proofs = engine.prove_exhaustive().unwrap();
+        assert_eq!(all_proofs.len(), 2, "The exhaustive API should report both proofs");
+        assert_eq!(engine.prove().unwrap().len(), 1, "prove reports each statement once");
+
+        let best = engine.prove_best().unwrap();
+        assert_eq!(best.len(), 1, "Should keep a single proof per statement");
+        let Proof { statement: stmt, chain, .. } = &best[0];
+        assert_eq!(*stmt, HashableStatement::Gt(make_anchored_key("X", "value"), make_anchored_key("Y", "value")));
+        // The known statement is cheaper than GtFromEntries, so it is just copied in
+        assert_eq!(*chain, vec![(NativeOperation::CopyStatement as u8, vec![stmt.clone()], stmt.clone())]);
+    }
+
+    #[test]
+    fn test_wildcard_right_operand() {
+        let mut engine = DeductionEngine::new();
+
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("me", "age"), HashableValue::Int(21))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("bar", "threshold"), HashableValue::Int(18))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("casino", "threshold"), HashableValue::Int(25))).unwrap();
+
+        // Which pod's threshold is below my age?
+        let target = WildcardStatement::Gt(
+            make_anchored_key("me", "age").into(),
+            WildcardAnchoredKey::wildcard("threshold".to_string(), "venue"),
+        );
+        engine.set_target(target);
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Only the bar's threshold is below 21");
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
+        engine.print_proof(stmt.clone(), chain.clone());
+        assert_eq!(
+            *stmt,
+            HashableStatement::Gt(make_anchored_key("me", "age"), make_anchored_key("bar", "threshold"))
+        );
+        assert_eq!(deduction_steps(chain)[0].0, NativeOperation::GtFromEntries as u8);
+    }
+
+    #[test]
+    fn test_wildcard_both_operands() {
+        let mut engine = DeductionEngine::new();
+
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "income"), HashableValue::Int(50))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "expenses"), HashableValue::Int(40))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "income"), HashableValue::Int(30))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "expenses"), HashableValue::Int(45))).unwrap();
+
+        // Different names: any income above any expenses
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("income".to_string(), "p"),
+            WildcardAnchoredKey::wildcard("expenses".to_string(), "q"),
+        ));
+        let proofs = engine.prove().unwrap();
+        let mut proved: Vec<String> = proofs.iter().map(|proof| proof.statement.to_string()).collect();
+        proved.sort();
+        proved.dedup();
+        assert_eq!(proved.len(), 2, "A:income exceeds both A's and B's expenses");
+
+        // The same name on both sides must bind the same pod
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "income"), HashableValue::Int(50))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "expenses"), HashableValue::Int(40))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "income"), HashableValue::Int(30))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "expenses"), HashableValue::Int(45))).unwrap();
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("income".to_string(), "p"),
+            WildcardAnchoredKey::wildcard("expenses".to_string(), "p"),
+        ));
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Only A earns more than it spends");
+        assert_eq!(
+            proofs[0].statement,
+            HashableStatement::Gt(make_anchored_key("A", "income"), make_anchored_key("A", "expenses"))
+        );
+    }
+
+    #[test]
+    fn test_proof_bindings() {
+        let mut engine = DeductionEngine::new();
+
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score"), HashableValue::Int(9))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "score"), HashableValue::Int(4))).unwrap();
+
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("score".to_string(), "winner"),
+            WildcardAnchoredKey::wildcard("score".to_string(), "loser"),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Only A beats B");
+        let bindings = &proofs[0].bindings;
+        assert_eq!(bindings.len(), 2, "Both named wildcards should be bound");
+        assert_eq!(bindings["winner"], Binding { origin: make_signed_origin("A"), key: None, kind: BindingKind::Origin });
+        assert_eq!(bindings["loser"], Binding { origin: make_signed_origin("B"), key: None, kind: BindingKind::Origin });
+
+        // Concrete operands contribute no bindings
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score"), HashableValue::Int(9))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "score"), HashableValue::Int(4))).unwrap();
+        let proofs = engine.prove_multiple(vec![WildcardStatement::Gt(
+            make_anchored_key("A", "score").into(),
+            make_anchored_key("B", "score").into(),
+        )]).unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert!(proofs[0].bindings.is_empty());
+    }
+
+    #[test]
+    fn test_key_wildcards() {
+        let mut engine = DeductionEngine::new();
+
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score_math"), HashableValue::Int(9))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score_art"), HashableValue::Int(3))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(40))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "limit"), HashableValue::Int(5))).unwrap();
+
+        // Any key of A greater than B's limit, with the key bound as "k"
+        let target = WildcardStatement::Gt(
+            WildcardAnchoredKey(WildcardId::Concrete(make_signed_origin("A")), KeyPattern::Named("k".to_string())),
+            make_anchored_key("B", "limit").into(),
+        );
+        engine.set_target(target.clone());
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 2, "score_math and age both exceed the limit");
+        for proof in &proofs {
+            let binding = &proof.bindings["k"];
+            assert_eq!(binding.origin, make_signed_origin("A"));
+            let HashableStatement::Gt(found, _) = &proof.statement else { panic!("Expected a Gt proof") };
+            assert_eq!(binding.key.as_ref(), Some(&found.1), "The bound key should be the one used in the proof");
+        }
+
+        // A prefix only considers score_* keys
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score_math"), HashableValue::Int(9))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(40))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "limit"), HashableValue::Int(5))).unwrap();
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey(WildcardId::Named("n".to_string()), KeyPattern::Prefix("score_".to_string())),
+            make_anchored_key("B", "limit").into(),
+        ));
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].statement, HashableStatement::Gt(make_anchored_key("A", "score_math"), make_anchored_key("B", "limit")));
+        assert_eq!(proofs[0].bindings["n"], Binding { origin: make_signed_origin("A"), key: Some("score_math".to_string()), kind: BindingKind::Origin });
+    }
+
+    #[test]
+    fn test_origin_name_ignores_key_under_different_patterns() {
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "income_salary"), HashableValue::Int(100))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "expense_rent"), HashableValue::Int(50))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "expense_rent"), HashableValue::Int(10))).unwrap();
+
+        // ?p names the origin on both sides, so only the keys may differ
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey(WildcardId::Named("p".to_string()), KeyPattern::Prefix("income_".to_string())),
+            WildcardAnchoredKey(WildcardId::Named("p".to_string()), KeyPattern::Prefix("expense_".to_string())),
+        ));
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Only A's own income and expense share an origin");
+        assert_eq!(proofs[0].statement, HashableStatement::Gt(make_anchored_key("A", "income_salary"), make_anchored_key("A", "expense_rent")));
+        assert_eq!(proofs[0].bindings["p"].origin, make_signed_origin("A"));
+        assert!(proofs[0].bindings["p"].key.is_some(), "The key is still reported");
+    }
+
+    #[test]
+    fn test_key_patterns_use_indexes() {
+        let setup = || {
+            let mut engine = DeductionEngine::new();
+            for i in 0..500 {
+                engine.add_fact(HashableStatement::ValueOf(make_anchored_key(&format!("pod{}", i), &format!("filler_{}", i)), HashableValue::Int(i))).unwrap();
+            }
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score_math"), HashableValue::Int(9))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(40))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "limit"), HashableValue::Int(5))).unwrap();
+            engine
+        };
+
+        // Any key of A is looked up by origin, and any score_* key by prefix, so
+        // neither compares the wildcard against the 500 unrelated entries
+        let mut engine = setup();
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey(WildcardId::Concrete(make_signed_origin("A")), KeyPattern::Named("k".to_string())),
+            make_anchored_key("B", "limit").into(),
+        ));
+        assert_eq!(engine.prove().unwrap().len(), 2);
+        assert!(engine.stats().candidate_matches < 50, "{}", engine.stats());
+
+        let mut engine = setup();
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey(WildcardId::Named("n".to_string()), KeyPattern::Prefix("score_".to_string())),
+            make_anchored_key("B", "limit").into(),
+        ));
+        assert_eq!(engine.prove().unwrap().len(), 1);
+        assert!(engine.stats().candidate_matches < 50, "{}", engine.stats());
+        assert_eq!(engine.stats().relation("name_prefix"), 501, "Key names of at least six characters are indexed under their prefix");
+    }
+
+    #[test]
+    fn test_key_glob_patterns() {
+        let glob = KeyPattern::Glob("score_*_202?".to_string());
+        assert!(glob.matches("score_math_2024"));
+        assert!(glob.matches("score__2020"));
+        assert!(!glob.matches("score_math_20245"));
+        assert!(!glob.matches("grade_math_2024"));
+        assert!(KeyPattern::Glob("*".to_string()).matches(""));
+
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score_math_2024"), HashableValue::Int(7))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "score_math_2019"), HashableValue::Int(7))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "target"), HashableValue::Int(7))).unwrap();
+        engine.set_target(WildcardStatement::Equal(
+            WildcardAnchoredKey(WildcardId::Concrete(make_signed_origin("A")), glob),
+            make_anchored_key("B", "target").into(),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Only the 2024 score matches the glob");
+        assert_eq!(proofs[0].statement, HashableStatement::Equal(make_anchored_key("A", "score_math_2024"), make_anchored_key("B", "target")));
+    }
+
+    #[test]
+    fn test_value_of_targets() {
+        let setup = || {
+            let mut engine = DeductionEngine::new();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "role"), HashableValue::String("admin".to_string()))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "role"), HashableValue::String("user".to_string()))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(30))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "age"), HashableValue::Int(17))).unwrap();
+            engine
+        };
+
+        // Which pod has role = admin?
+        let mut engine = setup();
+        engine.set_target(WildcardStatement::ValueOf(
+            WildcardAnchoredKey::wildcard("role".to_string(), "n"),
+            HashableValue::String("admin".to_string()).into(),
+        ));
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        let admin = HashableStatement::ValueOf(make_anchored_key("A", "role"), HashableValue::String("admin".to_string()));
+        assert_eq!(proofs[0].statement, admin);
+        assert_eq!(proofs[0].chain, vec![(NativeOperation::CopyStatement as u8, vec![admin.clone()], admin)]);
+        assert_eq!(proofs[0].bindings["n"].origin, make_signed_origin("A"));
+
+        // Any pod with an age entry
+        let mut engine = setup();
+        engine.set_target(WildcardStatement::ValueOf(WildcardAnchoredKey::wildcard("age".to_string(), "n"), ValuePattern::Any));
+        assert_eq!(engine.prove().unwrap().len(), 2);
+
+        // Adults only
+        let mut engine = setup();
+        engine.set_target(WildcardStatement::ValueOf(
+            WildcardAnchoredKey::wildcard("age".to_string(), "n"),
+            ValuePattern::IntRange(18, 150),
+        ));
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].statement, HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(30)));
+
+        // Patterns never match values of the wrong type
+        assert!(!ValuePattern::IntRange(0, 10).matches(&HashableValue::String("5".to_string())));
+        assert!(ValuePattern::StringPrefix("ad".to_string()).matches(&HashableValue::String("admin".to_string())));
+    }
+
+    #[test]
+    fn test_literal_operands() {
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(30))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "age"), HashableValue::Int(12))).unwrap();
+
+        // age > 18, with no existing entry holding 18
+        let literal = WildcardAnchoredKey::literal(HashableValue::Int(18));
+        let literal_key = literal.as_concrete().unwrap();
+        assert_eq!(literal_key.0, self_origin());
+        engine.set_target(WildcardStatement::Gt(WildcardAnchoredKey::wildcard("age".to_string(), "n"), literal));
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Only A is over 18");
+        let Proof { statement: stmt, chain, bindings } = &proofs[0];
+        assert_eq!(*stmt, HashableStatement::Gt(make_anchored_key("A", "age"), literal_key.clone()));
+        assert_eq!(bindings["n"].origin, make_signed_origin("A"));
+        let new_entry = HashableStatement::ValueOf(literal_key.clone(), HashableValue::Int(18));
+        let age_entry = HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(30));
+        assert_eq!(chain.len(), 3);
+        assert_eq!(chain[0], (NativeOperation::CopyStatement as u8, vec![age_entry.clone()], age_entry));
+        assert_eq!(chain[1], (NativeOperation::NewEntry as u8, vec![], new_entry.clone()));
+        assert_eq!(chain[2].0, NativeOperation::GtFromEntries as u8);
+        assert!(chain[2].1.contains(&new_entry));
+
+        // Equality against a literal string uses EqualFromEntries
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "country"), HashableValue::String("FR".to_string()))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("B", "country"), HashableValue::String("DE".to_string()))).unwrap();
+        engine.set_target(WildcardStatement::Equal(
+            WildcardAnchoredKey::wildcard("country".to_string(), "n"),
+            WildcardAnchoredKey::literal(HashableValue::String("FR".to_string())),
+        ));
+        let proofs = engine.prove_best().unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].bindings["n"].origin, make_signed_origin("A"));
+        assert!(proofs[0].chain.iter().any(|(op_code, _, _)| *op_code == NativeOperation::NewEntry as u8));
+        assert_eq!(proofs[0].chain.last().unwrap().0, NativeOperation::EqualFromEntries as u8);
+    }
+
+    #[test]
+    fn test_range_target() {
+        let setup = || {
+            let mut engine = DeductionEngine::new();
+            for (pod, age) in [("A", 17), ("B", 18), ("C", 40), ("D", 65), ("E", 70)] {
+                engine.add_fact(HashableStatement::ValueOf(make_anchored_key(pod, "age"), HashableValue::Int(age))).unwrap();
+            }
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("limits", "retirement"), HashableValue::Int(65))).unwrap();
+            engine
+        };
+        let age = || WildcardAnchoredKey::wildcard("age".to_string(), "n");
+        let adult = || Bound::inclusive(WildcardAnchoredKey::literal(HashableValue::Int(18)));
+
+        // 18 <= age < retirement
+        let mut engine = setup();
+        let proofs = engine.prove_range(&RangeQuery {
+            key: age(),
+            lower: adult(),
+            upper: Bound::exclusive(make_anchored_key("limits", "retirement")),
+        }).unwrap();
+        let mut in_range: Vec<_> = proofs.iter().map(|proof| proof.bindings["n"].origin.clone()).collect();
+        in_range.sort_by_key(|origin| format!("{:?}", origin));
+        let mut expected = vec![make_signed_origin("B"), make_signed_origin("C")];
+        expected.sort_by_key(|origin| format!("{:?}", origin));
+        assert_eq!(in_range, expected);
+
+        for proof in &proofs {
+            assert_eq!(proof.statements.len(), 2, "A range proof covers both ends");
+            assert!(matches!(proof.statements[0], HashableStatement::Gt(..) | HashableStatement::Equal(..)));
+            assert!(matches!(proof.statements[1], HashableStatement::Lt(..)));
+        }
+
+        // An inclusive upper bound admits the retirement age itself
+        let mut engine = setup();
+        let proofs = engine.prove_range(&RangeQuery {
+            key: age(),
+            lower: adult(),
+            upper: Bound::inclusive(make_anchored_key("limits", "retirement")),
+        }).unwrap();
+        assert_eq!(proofs.len(), 3);
+
+        // Without named wildcards, both ends must still be about the same key
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("P", "score_a"), HashableValue::Int(10))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("P", "score_b"), HashableValue::Int(50))).unwrap();
+        let proofs = engine.prove_range(&RangeQuery {
+            key: WildcardAnchoredKey(WildcardId::Concrete(make_signed_origin("P")), KeyPattern::Prefix("score_".to_string())),
+            lower: Bound::exclusive(WildcardAnchoredKey::literal(HashableValue::Int(5))),
+            upper: Bound::exclusive(WildcardAnchoredKey::literal(HashableValue::Int(30))),
+        }).unwrap();
+        assert_eq!(proofs.len(), 1, "score_b is above 5 but not below 30");
+        let score_a = make_anchored_key("P", "score_a");
+        assert!(matches!(&proofs[0].statements[0], HashableStatement::Gt(key, _) if *key == score_a));
+        assert!(matches!(&proofs[0].statements[1], HashableStatement::Lt(key, _) if *key == score_a));
+
+        // A key that is a wildcard throughout ranges over real entries, never the
+        // self entries backing the literal bounds
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(30))).unwrap();
+        let proofs = engine.prove_range(&RangeQuery {
+            key: WildcardAnchoredKey(WildcardId::Named("p".to_string()), KeyPattern::Named("k".to_string())),
+            lower: Bound::exclusive(WildcardAnchoredKey::literal(HashableValue::Int(18))),
+            upper: Bound::exclusive(WildcardAnchoredKey::literal(HashableValue::Int(65))),
+        }).unwrap();
+        assert_eq!(proofs.len(), 1, "Only A:age lies between the bounds");
+        assert_eq!(proofs[0].bindings["p"], Binding { origin: make_signed_origin("A"), key: Some("age".to_string()), kind: BindingKind::Origin });
+        let age = make_anchored_key("A", "age");
+        assert!(matches!(&proofs[0].statements[0], HashableStatement::Gt(key, _) if *key == age));
+        assert!(matches!(&proofs[0].statements[1], HashableStatement::Lt(key, _) if *key == age));
+    }
+
+    #[test]
+    fn test_copy_statements_for_known_facts() {
+        let mut engine = DeductionEngine::new();
+        let gt = HashableStatement::Gt(make_anchored_key("X", "value"), make_anchored_key("Y", "value"));
+        engine.add_fact(gt.clone()).unwrap();
+
+        // A target that is already known is proven by copying it
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
+        ));
+        let copy_gt = (NativeOperation::CopyStatement as u8, vec![gt.clone()], gt.clone());
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(proofs[0].chain, vec![copy_gt.clone()]);
+
+        // Known statements used as inputs are copied in ahead of the step using them
+        let neq_target = WildcardStatement::NotEqual(
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
+        );
+        engine.set_target(neq_target);
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        let neq = HashableStatement::NotEqual(make_anchored_key("X", "value"), make_anchored_key("Y", "value"));
+        assert_eq!(proofs[0].chain, vec![copy_gt, (NativeOperation::GtToNotEqual as u8, vec![gt], neq)]);
+
+        // An entry used by several statements of a query is copied once
+        let mut engine = DeductionEngine::new();
+        let age = HashableStatement::ValueOf(make_anchored_key("A", "age"), HashableValue::Int(30));
+        engine.add_fact(age.clone()).unwrap();
+        let proofs = engine.prove_query(&ConjunctiveQuery(vec![
+            WildcardStatement::Gt(WildcardAnchoredKey::wildcard("age".to_string(), "n"), WildcardAnchoredKey::literal(HashableValue::Int(18))),
+            WildcardStatement::Lt(WildcardAnchoredKey::wildcard("age".to_string(), "n"), WildcardAnchoredKey::literal(HashableValue::Int(65))),
+        ])).unwrap();
+        assert_eq!(proofs.len(), 1);
+        let copies = proofs[0].chain.iter()
+            .filter(|step| **step == (NativeOperation::CopyStatement as u8, vec![age.clone()], age.clone()))
+            .count();
+        assert_eq!(copies, 1);
+    }
+
     #[test]
     fn test_wildcard_lt() {
         let mut engine = DeductionEngine::new();
@@ -122,35 +1244,35 @@ mod tests {
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("X", "value"),
             HashableValue::Int(5),
-        ));
+        )).unwrap();
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("Y", "value"),
             HashableValue::Int(10),
-        ));
+        )).unwrap();
 
         // Add a direct LT statement
         engine.add_fact(HashableStatement::Lt(
             make_anchored_key("A", "value"),
             make_anchored_key("B", "value"),
-        ));
+        )).unwrap();
 
         // Test case 1: Find LT through value comparison
         let target = WildcardStatement::Lt(
-            WildcardAnchoredKey(WildcardId::Named("n".to_string()), "value".to_string()),
-            make_anchored_key("Y", "value"),
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
         );
         engine.set_target(target.clone());
 
-        let proofs = engine.prove();
+        let proofs = engine.prove().unwrap();
         assert!(!proofs.is_empty(), "Should find X < Y through value comparison");
-        let (stmt, chain) = &proofs[0];
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
         
         // Print the proof in a readable format
         engine.print_proof(stmt.clone(), chain.clone());
         
-        assert_eq!(chain.len(), 1, "Should use LtFromEntries");
+        assert_eq!(deduction_steps(chain).len(), 1, "Should use LtFromEntries");
         assert_eq!(
-            chain[0].0,
+            deduction_steps(chain)[0].0,
             NativeOperation::LtFromEntries as u8,
             "Should use LtFromEntries operation"
         );
@@ -173,31 +1295,31 @@ mod tests {
         engine.add_fact(HashableStatement::Gt(
             make_anchored_key("X", "value"),
             make_anchored_key("Y", "value"),
-        ));
+        )).unwrap();
 
         // Add a direct NEq statement
         engine.add_fact(HashableStatement::NotEqual(
             make_anchored_key("A", "value"),
             make_anchored_key("B", "value"),
-        ));
+        )).unwrap();
 
         // Test case 1: Find NEq through GT conversion
         let target = WildcardStatement::NotEqual(
-            WildcardAnchoredKey(WildcardId::Named("n".to_string()), "value".to_string()),
-            make_anchored_key("Y", "value"),
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
         );
         engine.set_target(target.clone());
 
-        let proofs = engine.prove();
+        let proofs = engine.prove().unwrap();
         assert!(!proofs.is_empty(), "Should find X != Y through GT conversion");
-        let (stmt, chain) = &proofs[0];
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
         
         // Print the proof in a readable format
         engine.print_proof(stmt.clone(), chain.clone());
         
-        assert_eq!(chain.len(), 1, "Should use GtToNotEqual");
+        assert_eq!(deduction_steps(chain).len(), 1, "Should use GtToNotEqual");
         assert_eq!(
-            chain[0].0,
+            deduction_steps(chain)[0].0,
             NativeOperation::GtToNotEqual as u8,
             "Should use GtToNotEqual operation"
         );
@@ -220,31 +1342,31 @@ mod tests {
         engine.add_fact(HashableStatement::Lt(
             make_anchored_key("X", "value"),
             make_anchored_key("Y", "value"),
-        ));
+        )).unwrap();
 
         // Add a direct NEq statement
         engine.add_fact(HashableStatement::NotEqual(
             make_anchored_key("A", "value"),
             make_anchored_key("B", "value"),
-        ));
+        )).unwrap();
 
         // Test case 1: Find NEq through LT conversion
         let target = WildcardStatement::NotEqual(
-            WildcardAnchoredKey(WildcardId::Named("n".to_string()), "value".to_string()),
-            make_anchored_key("Y", "value"),
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
         );
         engine.set_target(target.clone());
 
-        let proofs = engine.prove();
+        let proofs = engine.prove().unwrap();
         assert!(!proofs.is_empty(), "Should find X != Y through LT conversion");
-        let (stmt, chain) = &proofs[0];
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
         
         // Print the proof in a readable format
         engine.print_proof(stmt.clone(), chain.clone());
         
-        assert_eq!(chain.len(), 1, "Should use LtToNotEqual");
+        assert_eq!(deduction_steps(chain).len(), 1, "Should use LtToNotEqual");
         assert_eq!(
-            chain[0].0,
+            deduction_steps(chain)[0].0,
             NativeOperation::LtToNotEqual as u8,
             "Should use LtToNotEqual operation"
         );
@@ -259,6 +1381,108 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_wildcard_neq_from_entries() {
+        let mut engine = DeductionEngine::new();
+
+        // Two pods with different string values for the same key
+        engine.add_fact(HashableStatement::ValueOf(
+            make_anchored_key("X", "country"),
+            HashableValue::String("Freedonia".to_string()),
+        )).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(
+            make_anchored_key("Y", "country"),
+            HashableValue::String("Sylvania".to_string()),
+        )).unwrap();
+
+        let target = WildcardStatement::NotEqual(
+            WildcardAnchoredKey::wildcard("country".to_string(), "n"),
+            make_anchored_key("Y", "country").into(),
+        );
+        engine.set_target(target.clone());
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Only X should differ from Y");
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
+
+        // Print the proof in a readable format
+        engine.print_proof(stmt.clone(), chain.clone());
+
+        assert_eq!(deduction_steps(chain).len(), 1, "Should use NotEqualFromEntries");
+        assert_eq!(
+            deduction_steps(chain)[0].0,
+            NativeOperation::NotEqualFromEntries as u8,
+            "Should use NotEqualFromEntries operation"
+        );
+        assert_eq!(
+            *stmt,
+            HashableStatement::NotEqual(make_anchored_key("X", "country"), make_anchored_key("Y", "country"))
+        );
+    }
+
+    #[test]
+    fn test_wildcard_neq_from_container_entries() {
+        let mut engine = DeductionEngine::new();
+
+        let arr1 = MiddlewareArray::new(&vec![MiddlewareValue::from(1i64), MiddlewareValue::from(2i64)]).unwrap();
+        let arr2 = MiddlewareArray::new(&vec![MiddlewareValue::from(1i64), MiddlewareValue::from(3i64)]).unwrap();
+
+        engine.add_fact(HashableStatement::ValueOf(
+            make_anchored_key("X", "list"),
+            HashableValue::Array(arr1.clone()),
+        )).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(
+            make_anchored_key("Z", "list"),
+            HashableValue::Array(arr1),
+        )).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(
+            make_anchored_key("Y", "list"),
+            HashableValue::Array(arr2),
+        )).unwrap();
+
+        let target = WildcardStatement::NotEqual(
+            WildcardAnchoredKey::wildcard("list".to_string(), "n"),
+            make_anchored_key("Y", "list").into(),
+        );
+        engine.set_target(target);
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 2, "Both X and Z hold a different array than Y");
+        for Proof { chain, .. } in &proofs {
+            assert_eq!(deduction_steps(chain)[0].0, NativeOperation::NotEqualFromEntries as u8);
+        }
+    }
+
+    #[test]
+    fn test_entries_compare_as_native_values() {
+        // Bool(true) and Int(1) are the same value to a pod
+        let facts = vec![
+            HashableStatement::ValueOf(make_anchored_key("A", "flag"), HashableValue::Bool(true)),
+            HashableStatement::ValueOf(make_anchored_key("B", "count"), HashableValue::Int(1)),
+        ];
+        let flag = || WildcardAnchoredKey::from(make_anchored_key("A", "flag"));
+        let count = || WildcardAnchoredKey::from(make_anchored_key("B", "count"));
+        let mut engine = DeductionEngine::new();
+        for fact in &facts {
+            engine.add_fact(fact.clone()).unwrap();
+        }
+
+        engine.set_target(WildcardStatement::NotEqual(flag(), count()));
+        assert!(matches!(engine.prove(), Err(ProverError::NoProof(_))), "true and 1 must not be proven different");
+
+        engine.set_target(WildcardStatement::Equal(flag(), count()));
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(deduction_steps(&proofs[0].chain)[0].0, NativeOperation::EqualFromEntries as u8);
+        assert_eq!(verify_chain(&facts, &proofs[0].chain, &proofs[0].statement), Ok(()));
+
+        // The checker agrees: NotEqualFromEntries does not hold for them
+        let not_equal = HashableStatement::NotEqual(make_anchored_key("A", "flag"), make_anchored_key("B", "count"));
+        let copy = |stmt: &HashableStatement| (NativeOperation::CopyStatement as u8, vec![stmt.clone()], stmt.clone());
+        let chain = vec![copy(&facts[0]), copy(&facts[1]), (NativeOperation::NotEqualFromEntries as u8, facts.clone(), not_equal.clone())];
+        assert_eq!(verify_chain(&facts, &chain, &not_equal), Err(ChainError::UnsatisfiedStep { step: 2, operation: "NotEqualFromEntries" }));
+    }
+
     #[test]
     fn test_wildcard_contains() {
         let mut engine = DeductionEngine::new();
@@ -274,35 +1498,35 @@ mod tests {
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("X", "value"),
             HashableValue::Array(arr),
-        ));
+        )).unwrap();
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("Y", "value"),
             HashableValue::Int(2),
-        ));
+        )).unwrap();
 
         // Add a direct Contains statement
         engine.add_fact(HashableStatement::Contains(
             make_anchored_key("A", "value"),
             make_anchored_key("B", "value"),
-        ));
+        )).unwrap();
 
         // Test case 1: Find Contains through value comparison
         let target = WildcardStatement::Contains(
-            WildcardAnchoredKey(WildcardId::Named("n".to_string()), "value".to_string()),
-            make_anchored_key("Y", "value"),
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
         );
         engine.set_target(target.clone());
 
-        let proofs = engine.prove();
+        let proofs = engine.prove().unwrap();
         assert!(!proofs.is_empty(), "Should find X contains Y through value comparison");
-        let (stmt, chain) = &proofs[0];
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
         
         // Print the proof in a readable format
         engine.print_proof(stmt.clone(), chain.clone());
         
-        assert_eq!(chain.len(), 1, "Should use ContainsFromEntries");
+        assert_eq!(deduction_steps(chain).len(), 1, "Should use ContainsFromEntries");
         assert_eq!(
-            chain[0].0,
+            deduction_steps(chain)[0].0,
             NativeOperation::ContainsFromEntries as u8,
             "Should use ContainsFromEntries operation"
         );
@@ -332,22 +1556,305 @@ mod tests {
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("X", "value"),
             HashableValue::Array(arr),
-        ));
+        )).unwrap();
         // Add a value that is NOT in the array
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("Y", "value"),
             HashableValue::Int(4),
-        ));
+        )).unwrap();
 
         // Try to prove that X contains Y (which should be impossible)
         let target = WildcardStatement::Contains(
-            WildcardAnchoredKey(WildcardId::Named("n".to_string()), "value".to_string()),
-            make_anchored_key("Y", "value"),
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
         );
         engine.set_target(target.clone());
 
         let proofs = engine.prove();
-        assert!(proofs.is_empty(), "Should NOT be able to prove X contains Y since 4 is not in the array");
+        assert_eq!(proofs, Err(ProverError::NoProof(target)), "Should NOT be able to prove X contains Y since 4 is not in the array");
+    }
+
+    #[test]
+    fn test_wildcard_not_contains() {
+        // A revocation list holding ids 1, 2, 3, plus two ids to check against it
+        let setup = || {
+            let mut engine = DeductionEngine::new();
+            let values = vec![
+                MiddlewareValue::from(1i64),
+                MiddlewareValue::from(2i64),
+                MiddlewareValue::from(3i64),
+            ];
+            let arr = MiddlewareArray::new(&values).unwrap();
+
+            engine.add_fact(HashableStatement::ValueOf(
+                make_anchored_key("X", "revoked"),
+                HashableValue::Array(arr),
+            )).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(
+                make_anchored_key("Y", "id"),
+                HashableValue::Int(4),
+            )).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(
+                make_anchored_key("Z", "id"),
+                HashableValue::Int(2),
+            )).unwrap();
+            engine
+        };
+
+        let mut engine = setup();
+        engine.set_target(WildcardStatement::NotContains(
+            WildcardAnchoredKey::wildcard("revoked".to_string(), "n"),
+            make_anchored_key("Y", "id").into(),
+        ));
+
+        let proofs = engine.prove().unwrap();
+        assert_eq!(proofs.len(), 1, "Should find X does not contain Y");
+        let Proof { statement: stmt, chain, .. } = &proofs[0];
+
+        // Print the proof in a readable format
+        engine.print_proof(stmt.clone(), chain.clone());
+
+        assert_eq!(deduction_steps(chain).len(), 1, "Should use NotContainsFromEntries");
+        assert_eq!(
+            deduction_steps(chain)[0].0,
+            NativeOperation::NotContainsFromEntries as u8,
+            "Should use NotContainsFromEntries operation"
+        );
+        assert_eq!(
+            *stmt,
+            HashableStatement::NotContains(make_anchored_key("X", "revoked"), make_anchored_key("Y", "id"))
+        );
+
+        // Z's id is in the list, so the same query against it must fail
+        let mut engine = setup();
+        engine.set_target(WildcardStatement::NotContains(
+            WildcardAnchoredKey::wildcard("revoked".to_string(), "n"),
+            make_anchored_key("Z", "id").into(),
+        ));
+        let proofs = engine.prove();
+        assert!(matches!(proofs, Err(ProverError::NoProof(_))), "Should NOT prove X does not contain Z since 2 is in the array");
+    }
+
+    #[test]
+    fn test_wildcard_arithmetic() {
+        let total = || WildcardAnchoredKey::wildcard("total".to_string(), "n");
+        let cases = vec![
+            (
+                WildcardStatement::SumOf(total(), make_anchored_key("P", "salary"), make_anchored_key("P", "bonus")),
+                NativeOperation::SumOf as u8,
+                "X",
+            ),
+            (
+                WildcardStatement::ProductOf(total(), make_anchored_key("P", "salary"), make_anchored_key("P", "bonus")),
+                NativeOperation::ProductOf as u8,
+                "Y",
+            ),
+            (
+                WildcardStatement::MaxOf(total(), make_anchored_key("P", "salary"), make_anchored_key("P", "bonus")),
+                NativeOperation::MaxOf as u8,
+                "Z",
+            ),
+        ];
+
+        for (target, op_code, expected_origin) in cases {
+            // salary + bonus = 120, salary * bonus = 2000, max(salary, bonus) = 100
+            let mut engine = DeductionEngine::new();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("P", "salary"), HashableValue::Int(100))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("P", "bonus"), HashableValue::Int(20))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("X", "total"), HashableValue::Int(120))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("Y", "total"), HashableValue::Int(2000))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key("Z", "total"), HashableValue::Int(100))).unwrap();
+            engine.set_target(target);
+
+            let proofs = engine.prove().unwrap();
+            assert_eq!(proofs.len(), 1, "Exactly one total should match");
+            let Proof { statement: stmt, chain, .. } = &proofs[0];
+            engine.print_proof(stmt.clone(), chain.clone());
+
+            assert_eq!(deduction_steps(chain).len(), 1);
+            assert_eq!(deduction_steps(chain)[0].0, op_code);
+            assert_eq!(deduction_steps(chain)[0].1.len(), 3, "Should use the three entry values");
+            match stmt {
+                HashableStatement::SumOf(found_key, _, _)
+                | HashableStatement::ProductOf(found_key, _, _)
+                | HashableStatement::MaxOf(found_key, _, _) => {
+                    assert_eq!(*found_key, make_anchored_key(expected_origin, "total"));
+                },
+                _ => panic!("Expected an arithmetic statement"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_wildcard_sum_overflow() {
+        let mut engine = DeductionEngine::new();
+
+        // i64::MAX + 1 would wrap around to i64::MIN
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("P", "a"), HashableValue::Int(i64::MAX))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("P", "b"), HashableValue::Int(1))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("X", "sum"), HashableValue::Int(i64::MIN))).unwrap();
+
+        engine.set_target(WildcardStatement::SumOf(
+            WildcardAnchoredKey::wildcard("sum".to_string(), "n"),
+            make_anchored_key("P", "a"),
+            make_anchored_key("P", "b"),
+        ));
+
+        let proofs = engine.prove();
+        assert!(matches!(proofs, Err(ProverError::NoProof(_))), "Overflowing sums must not be provable");
+    }
+
+    #[test]
+    fn test_multiple_targets_single_run() {
+        let mut engine = DeductionEngine::new();
+
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("X", "age"), HashableValue::Int(30))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("Y", "age"), HashableValue::Int(18))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("Y", "limit"), HashableValue::Int(65))).unwrap();
+
+        let gt_target = WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("age".to_string(), "n"),
+            make_anchored_key("Y", "age").into(),
+        );
+        let lt_target = WildcardStatement::Lt(
+            WildcardAnchoredKey::wildcard("age".to_string(), "n"),
+            make_anchored_key("Y", "limit").into(),
+        );
+        engine.add_target(gt_target.clone());
+        engine.add_target(lt_target.clone());
+
+        // Each result is tagged with the target it satisfies
+        let gt_proofs = engine.proofs_for(&gt_target).unwrap();
+        assert_eq!(gt_proofs.len(), 1, "Only X is older than Y");
+        assert_eq!(gt_proofs[0].statement, HashableStatement::Gt(make_anchored_key("X", "age"), make_anchored_key("Y", "age")));
+
+        let lt_proofs = engine.proofs_for(&lt_target).unwrap();
+        assert_eq!(lt_proofs.len(), 2, "Both X and Y are under the limit");
+        for Proof { statement: stmt, chain, .. } in &lt_proofs {
+            assert!(matches!(stmt, HashableStatement::Lt(_, _)));
+            assert_eq!(deduction_steps(chain)[0].0, NativeOperation::LtFromEntries as u8);
+        }
+
+        assert_eq!(engine.prove().unwrap().len(), 3, "prove() reports the proofs of every target");
+    }
+
+    #[test]
+    fn test_set_target_replaces_earlier_targets() {
+        let mut engine = DeductionEngine::new();
+        engine.add_fact(HashableStatement::Gt(make_anchored_key("X", "value"), make_anchored_key("Y", "value"))).unwrap();
+        engine.set_target(WildcardStatement::Gt(
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
+        ));
+        assert_eq!(engine.prove().unwrap().len(), 1);
+
+        // The earlier target's proof is not reported for its replacement
+        let lt_target = WildcardStatement::Lt(
+            WildcardAnchoredKey::wildcard("value".to_string(), "n"),
+            make_anchored_key("Y", "value").into(),
+        );
+        engine.set_target(lt_target.clone());
+        assert_eq!(engine.prove(), Err(ProverError::NoProof(lt_target)));
+        assert!(engine.prove_best().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_incremental_facts_match_fresh_run() {
+        let target = WildcardStatement::Equal(
+            WildcardAnchoredKey::concrete(make_signed_origin("a"), "v".to_string()),
+            make_anchored_key("e", "v").into(),
+        );
+        let first_batch = vec![
+            HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("b", "v")),
+            HashableStatement::Equal(make_anchored_key("b", "v"), make_anchored_key("c", "v")),
+            HashableStatement::ValueOf(make_anchored_key("d", "v"), HashableValue::Int(3)),
+        ];
+        let second_batch = vec![
+            HashableStatement::Equal(make_anchored_key("c", "v"), make_anchored_key("d", "v")),
+            HashableStatement::ValueOf(make_anchored_key("e", "v"), HashableValue::Int(3)),
+            // A shortcut that makes the earlier chain no longer the best one
+            HashableStatement::Equal(make_anchored_key("a", "v"), make_anchored_key("c", "v")),
+        ];
+
+        let mut incremental = DeductionEngine::new();
+        for fact in &first_batch {
+            incremental.add_fact(fact.clone()).unwrap();
+        }
+        incremental.set_target(target.clone());
+        assert!(matches!(incremental.prove(), Err(ProverError::NoProof(_))), "a = e is not provable from the first batch");
+
+        // New signed pods arrive: add them without resetting
+        for fact in &second_batch {
+            incremental.add_fact(fact.clone()).unwrap();
+        }
+        let other_target = WildcardStatement::Equal(
+            WildcardAnchoredKey::concrete(make_signed_origin("b"), "v".to_string()),
+            make_anchored_key("d", "v").into(),
+        );
+        incremental.add_target(other_target.clone());
+        let incremental_best = incremental.prove_best().unwrap();
+
+        let mut fresh = DeductionEngine::new();
+        for fact in first_batch.iter().chain(second_batch.iter()) {
+            fresh.add_fact(fact.clone()).unwrap();
+        }
+        fresh.set_target(target);
+        fresh.add_target(other_target);
+        let fresh_best = fresh.prove_best().unwrap();
+
+        assert!(!fresh_best.is_empty(), "a = e should be provable once all facts are known");
+        assert_eq!(summarise(&incremental_best), summarise(&fresh_best));
+    }
+
+    #[test]
+    fn test_conjunctive_query_joins_named_wildcards() {
+        let mut engine = DeductionEngine::new();
+
+        // Reference values: minimum age, sanctioned country and the government signer
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("policy", "min_age"), HashableValue::Int(18))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("policy", "sanctioned"), HashableValue::String("Sylvania".to_string()))).unwrap();
+        engine.add_fact(HashableStatement::ValueOf(make_anchored_key("policy", "gov"), HashableValue::String("gov".to_string()))).unwrap();
+
+        // P1 satisfies everything, P2 is from the sanctioned country, P3 is too young
+        for (pod, age, country) in [("P1", 30, "Freedonia"), ("P2", 40, "Sylvania"), ("P3", 12, "Freedonia")] {
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key(pod, "age"), HashableValue::Int(age))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key(pod, "country"), HashableValue::String(country.to_string()))).unwrap();
+            engine.add_fact(HashableStatement::ValueOf(make_anchored_key(pod, "_signer"), HashableValue::String("gov".to_string()))).unwrap();
+        }
+
+        let query = ConjunctiveQuery(vec![
+            WildcardStatement::Gt(
+                WildcardAnchoredKey::wildcard("age".to_string(), "p"),
+                make_anchored_key("policy", "min_age").into(),
+            ),
+            WildcardStatement::NotEqual(
+                WildcardAnchoredKey::wildcard("country".to_string(), "p"),
+                make_anchored_key("policy", "sanctioned").into(),
+            ),
+            WildcardStatement::Equal(
+                WildcardAnchoredKey::wildcard("_signer".to_string(), "p"),
+                make_anchored_key("policy", "gov").into(),
+            ),
+        ]);
+
+        let results = engine.prove_query(&query).unwrap();
+        assert_eq!(results.len(), 1, "Only P1 satisfies all three statements");
+        let proof = &results[0];
+        assert_eq!(proof.bindings.get("p").map(|binding| &binding.origin), Some(&make_signed_origin("P1")));
+        assert_eq!(proof.statements.len(), 3, "Should report one statement per query statement");
+        assert_eq!(
+            proof.statements[0],
+            HashableStatement::Gt(make_anchored_key("P1", "age"), make_anchored_key("policy", "min_age"))
+        );
+        assert!(proof.chain.len() >= 3, "The combined chain should cover all three statements");
+
+        // The query's statements were only targets for that call
+        assert_eq!(engine.prove(), Ok(vec![]));
+        let policy_gov = WildcardStatement::ValueOf(make_anchored_key("policy", "gov").into(), ValuePattern::Any);
+        engine.set_target(policy_gov);
+        assert_eq!(engine.prove().unwrap().len(), 1);
+        assert_eq!(engine.prove_query(&query).unwrap().len(), 1);
+        assert_eq!(engine.prove().unwrap().len(), 1, "Querying again leaves the engine's target alone");
     }
 
     #[test]
@@ -358,44 +1865,44 @@ mod tests {
         engine.add_fact(HashableStatement::Equal(
             make_anchored_key("a", "value"),
             make_anchored_key("b", "value")
-        ));
+        )).unwrap();
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("b", "value"),
             HashableValue::Int(10)
-        ));
+        )).unwrap();
         engine.add_fact(HashableStatement::ValueOf(
             make_anchored_key("c", "value"),
             HashableValue::Int(10)
-        ));
+        )).unwrap();
         engine.add_fact(HashableStatement::Equal(
             make_anchored_key("c", "value"),
             make_anchored_key("d", "value")
-        ));
+        )).unwrap();
         
         let targets = vec![
             // First prove b = c (because they have the same value)
             WildcardStatement::Equal(
-                WildcardAnchoredKey(WildcardId::Concrete(make_signed_origin("b")), "value".to_string()),
-                make_anchored_key("c", "value")
+                WildcardAnchoredKey::concrete(make_signed_origin("b"), "value".to_string()),
+                make_anchored_key("c", "value").into()
             ),
             // Then we can prove a = d (using the chain a = b = c = d)
             WildcardStatement::Equal(
-                WildcardAnchoredKey(WildcardId::Concrete(make_signed_origin("a")), "value".to_string()),
-                make_anchored_key("d", "value")
+                WildcardAnchoredKey::concrete(make_signed_origin("a"), "value".to_string()),
+                make_anchored_key("d", "value").into()
             ),
         ];
 
-        let proofs = engine.prove_multiple(targets);
+        let proofs = engine.prove_multiple(targets).unwrap();
         assert_eq!(proofs.len(), 2, "Should prove both statements");
         
         // Print all proofs for debugging
-        for (i, (stmt, chain)) in proofs.iter().enumerate() {
+        for (i, Proof { statement: stmt, chain, .. }) in proofs.iter().enumerate() {
             println!("\nProof {}:", i + 1);
             engine.print_proof(stmt.clone(), chain.clone());
         }
         
         // First proof should be b = c
-        match &proofs[0].0 {
+        match &proofs[0].statement {
             HashableStatement::Equal(k1, k2) => {
                 assert_eq!(k1.1, "value");
                 assert_eq!(k2.1, "value");
@@ -407,7 +1914,7 @@ mod tests {
         }
         
         // Second proof should be a = d
-        match &proofs[1].0 {
+        match &proofs[1].statement {
             HashableStatement::Equal(k1, k2) => {
                 assert_eq!(k1.1, "value");
                 assert_eq!(k2.1, "value");
@@ -419,6 +1926,6 @@ mod tests {
         }
         
         // The second proof should have a non-empty chain showing the transitive steps
-        assert!(!proofs[1].1.is_empty(), "Second proof should require deduction steps");
+        assert!(!proofs[1].chain.is_empty(), "Second proof should require deduction steps");
     }
 }
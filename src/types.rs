@@ -1,8 +1,13 @@
-use pod2::middleware::NativeOperation;
+use pod2::middleware::{NativeOperation, SELF};
 use pod2::middleware::containers::{Dictionary, Set, Array};
-use pod2::frontend::{AnchoredKey, Origin};
+use pod2::frontend::{AnchoredKey, Origin, PodClass};
+use tracing::trace;
 
 
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
@@ -51,6 +56,51 @@ impl Hash for HashableValue {
 pub type DeductionStep = (u8, Vec<HashableStatement>, HashableStatement);
 pub type DeductionChain = Vec<DeductionStep>;
 
+// Index of a step within a ProofGraph
+pub type StepId = usize;
+
+// One node of a proof DAG: the operation that established a statement and the
+// steps establishing its premises. Known statements are CopyStatement steps and
+// literal entries NewEntry steps, both without premises.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofStep {
+    pub operation: u8,
+    pub premises: Vec<StepId>,
+    pub statement: HashableStatement,
+}
+
+// A proof in which every statement appears once, however many steps use it.
+// Steps are in dependency order, so the last one proves the root statement.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProofGraph {
+    pub steps: Vec<ProofStep>,
+}
+
+impl ProofGraph {
+    pub fn root(&self) -> Option<&HashableStatement> {
+        self.steps.last().map(|step| &step.statement)
+    }
+
+    // Flatten into the equivalent linear chain. Fails with the first statement no
+    // operation established (recorded with NativeOperation::None), as a chain
+    // missing that step would use a premise it never proves.
+    pub fn to_chain(&self) -> Result<DeductionChain, HashableStatement> {
+        self.steps.iter()
+            .map(|step| {
+                if step.operation == NativeOperation::None as u8 {
+                    return Err(step.statement.clone());
+                }
+                let inputs = if step.operation == NativeOperation::CopyStatement as u8 {
+                    vec![step.statement.clone()]
+                } else {
+                    step.premises.iter().map(|id| self.steps[*id].statement.clone()).collect()
+                };
+                Ok((step.operation, inputs, step.statement.clone()))
+            })
+            .collect()
+    }
+}
+
 // Helper function to format AnchoredKey
 fn format_anchored_key(ak: &AnchoredKey) -> String {
     format!("{}:{}", ak.0.1.to_string(), ak.1)  // Show both origin ID and key
@@ -91,8 +141,63 @@ impl fmt::Display for HashableStatement {
     }
 }
 
+// No native operation turns `b = a` into `a = b`, so the engine records using a
+// known equality the other way round as a step of its own. Its op code lies
+// outside pod2's; decode op codes with `Operation::from_code` rather than
+// comparing against it.
+const SYMMETRIC_EQUAL: u8 = u8::MAX;
+
+// The operation of a deduction step. Chains carry op codes as pods do; this
+// tells pod2's native operations apart from the engine's own step, which a pod
+// cannot replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    // A native operation, by its op code
+    Native(u8),
+    // A known equality used the other way round, after copying it in as stored
+    SymmetricEqual,
+}
+
+impl Operation {
+    // None if the code is neither a native operation nor the engine's own step
+    pub fn from_code(op_code: u8) -> Option<Self> {
+        match op_code {
+            SYMMETRIC_EQUAL => Some(Self::SymmetricEqual),
+            _ => native_operation_name(op_code).map(|_| Self::Native(op_code)),
+        }
+    }
+
+    pub const fn code(self) -> u8 {
+        match self {
+            Self::Native(op_code) => op_code,
+            Self::SymmetricEqual => SYMMETRIC_EQUAL,
+        }
+    }
+
+    pub fn is_native(self) -> bool {
+        matches!(self, Self::Native(_))
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Native(op_code) => operation_name(op_code),
+            Self::SymmetricEqual => "SymmetricEqual (not a native operation)",
+        }
+    }
+}
+
+// The name of a native operation
 pub fn operation_name(op_code: u8) -> &'static str {
-    match op_code {
+    native_operation_name(op_code).unwrap_or("Unknown Operation")
+}
+
+// The name of a step's operation, native or not
+pub fn step_name(op_code: u8) -> &'static str {
+    Operation::from_code(op_code).map_or("Unknown Operation", Operation::name)
+}
+
+fn native_operation_name(op_code: u8) -> Option<&'static str> {
+    let name = match op_code {
         x if x == NativeOperation::None as u8 => "None",
         x if x == NativeOperation::NewEntry as u8 => "NewEntry",
         x if x == NativeOperation::CopyStatement as u8 => "CopyStatement",
@@ -108,54 +213,585 @@ pub fn operation_name(op_code: u8) -> &'static str {
         x if x == NativeOperation::SumOf as u8 => "SumOf",
         x if x == NativeOperation::ProductOf as u8 => "ProductOf",
         x if x == NativeOperation::MaxOf as u8 => "MaxOf",
-        _ => "Unknown Operation"
-    }
-} 
+        _ => return None,
+    };
+    Some(name)
+}
 
 // The core wildcard type - represents either a concrete origin or a named wildcard
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum WildcardId {
     Concrete(Origin),
     Named(String),
+    // A constant rather than an existing entry; see `WildcardAnchoredKey::literal`
+    Literal(HashableValue),
 }
 
+// The origin of entries the prover introduces itself, such as literal operands
+pub fn self_origin() -> Origin {
+    Origin(PodClass::Main, SELF)
+}
+
+// Which key names a WildcardAnchoredKey accepts
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub struct WildcardAnchoredKey(pub WildcardId, pub String);
+pub enum KeyPattern {
+    // Exactly this key name
+    Exact(String),
+    // Any key name, bound under the given wildcard name
+    Named(String),
+    // Key names starting with a prefix, e.g. `score_` for `score_*`
+    Prefix(String),
+    // Key names matching a glob where `*` is any run of characters and `?` is one character
+    Glob(String),
+}
+
+impl KeyPattern {
+    pub fn matches(&self, key: &str) -> bool {
+        match self {
+            KeyPattern::Exact(exact) => exact == key,
+            KeyPattern::Named(_) => true,
+            KeyPattern::Prefix(prefix) => key.starts_with(prefix.as_str()),
+            KeyPattern::Glob(glob) => glob_matches(glob, key),
+        }
+    }
+
+    // The key name, if this pattern only accepts one
+    pub fn exact(&self) -> Option<String> {
+        match self {
+            KeyPattern::Exact(exact) => Some(exact.clone()),
+            _ => None,
+        }
+    }
+}
+
+impl From<String> for KeyPattern {
+    fn from(key: String) -> Self {
+        KeyPattern::Exact(key)
+    }
+}
+
+impl From<&str> for KeyPattern {
+    fn from(key: &str) -> Self {
+        KeyPattern::Exact(key.to_string())
+    }
+}
 
+impl fmt::Display for KeyPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyPattern::Exact(exact) => write!(f, "{}", exact),
+            KeyPattern::Named(name) => write!(f, "?{}", name),
+            KeyPattern::Prefix(prefix) => write!(f, "{}*", prefix),
+            KeyPattern::Glob(glob) => write!(f, "{}", glob),
+        }
+    }
+}
+
+// Glob matching with backtracking over the most recent `*`
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` absorb one more character and retry
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WildcardAnchoredKey(pub WildcardId, pub KeyPattern);
+
+// Which values a ValueOf target accepts
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ValuePattern {
+    // Any value at all, i.e. the entry just has to exist
+    Any,
+    Exact(HashableValue),
+    // Integers between the bounds, inclusive
+    IntRange(i64, i64),
+    OneOf(Vec<HashableValue>),
+    // Strings starting with a prefix
+    StringPrefix(String),
+}
+
+impl ValuePattern {
+    pub fn matches(&self, value: &HashableValue) -> bool {
+        match self {
+            ValuePattern::Any => true,
+            ValuePattern::Exact(exact) => exact == value,
+            ValuePattern::IntRange(min, max) => matches!(value, HashableValue::Int(i) if min <= i && i <= max),
+            ValuePattern::OneOf(values) => values.contains(value),
+            ValuePattern::StringPrefix(prefix) => matches!(value, HashableValue::String(s) if s.starts_with(prefix.as_str())),
+        }
+    }
+}
+
+impl From<HashableValue> for ValuePattern {
+    fn from(value: HashableValue) -> Self {
+        ValuePattern::Exact(value)
+    }
+}
+
+// Either operand of a binary statement may be a wildcard; use
+// `WildcardAnchoredKey::from(anchored_key)` for a concrete operand
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum WildcardStatement {
-    ValueOf(WildcardAnchoredKey, HashableValue),
-    Equal(WildcardAnchoredKey, AnchoredKey),
-    NotEqual(WildcardAnchoredKey, AnchoredKey),
-    Gt(WildcardAnchoredKey, AnchoredKey),
-    Lt(WildcardAnchoredKey, AnchoredKey),
-    Contains(WildcardAnchoredKey, AnchoredKey),
+    ValueOf(WildcardAnchoredKey, ValuePattern),
+    Equal(WildcardAnchoredKey, WildcardAnchoredKey),
+    NotEqual(WildcardAnchoredKey, WildcardAnchoredKey),
+    Gt(WildcardAnchoredKey, WildcardAnchoredKey),
+    Lt(WildcardAnchoredKey, WildcardAnchoredKey),
+    Contains(WildcardAnchoredKey, WildcardAnchoredKey),
+    NotContains(WildcardAnchoredKey, WildcardAnchoredKey),
+    SumOf(WildcardAnchoredKey, AnchoredKey, AnchoredKey),
+    ProductOf(WildcardAnchoredKey, AnchoredKey, AnchoredKey),
+    MaxOf(WildcardAnchoredKey, AnchoredKey, AnchoredKey),
+}
+
+// Which part of an anchored key a named wildcard stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BindingKind {
+    // The origin, from `WildcardId::Named`
+    Origin,
+    // The key name, from `KeyPattern::Named`
+    Key,
+}
+
+// What a named wildcard was bound to in a proof
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Binding {
+    pub origin: Origin,
+    // The key name, only reported when the key itself was a wildcard
+    pub key: Option<String>,
+    pub kind: BindingKind,
+}
+
+impl Binding {
+    // Two bindings of the same name conflict if they pick different values for
+    // what the name stands for: an origin name only constrains the origin, and a
+    // key name only the key name, whatever else is reported alongside. A name
+    // used for an origin in one place and a key in another must agree on both.
+    pub fn conflicts_with(&self, other: &Binding) -> bool {
+        match (self.kind, other.kind) {
+            (BindingKind::Origin, BindingKind::Origin) => self.origin != other.origin,
+            (BindingKind::Key, BindingKind::Key) => self.key != other.key,
+            _ => self.origin != other.origin || self.key != other.key,
+        }
+    }
+}
+
+// The binding chosen for each named wildcard
+pub type Bindings = HashMap<String, Binding>;
+
+// A proven statement, the chain proving it, and the bindings of the target's
+// named wildcards
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub statement: HashableStatement,
+    pub chain: DeductionChain,
+    pub bindings: Bindings,
+}
+
+// A conjunction of wildcard statements. A named wildcard shared between
+// statements must bind the same origin in all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConjunctiveQuery(pub Vec<WildcardStatement>);
+
+// One end of a range target
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bound {
+    pub operand: WildcardAnchoredKey,
+    pub inclusive: bool,
+}
+
+impl Bound {
+    pub fn inclusive(operand: impl Into<WildcardAnchoredKey>) -> Self {
+        Self { operand: operand.into(), inclusive: true }
+    }
+
+    pub fn exclusive(operand: impl Into<WildcardAnchoredKey>) -> Self {
+        Self { operand: operand.into(), inclusive: false }
+    }
+}
+
+// A target asserting `lower < key < upper` (or `<=` for inclusive bounds) for a
+// single anchored key. Bounds are usually literals or concrete keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RangeQuery {
+    pub key: WildcardAnchoredKey,
+    pub lower: Bound,
+    pub upper: Bound,
+}
+
+impl RangeQuery {
+    // The targets that satisfy the lower end: key > lower, or key = lower when inclusive
+    pub fn lower_targets(&self) -> Vec<WildcardStatement> {
+        let mut targets = vec![WildcardStatement::Gt(self.key.clone(), self.lower.operand.clone())];
+        if self.lower.inclusive {
+            targets.push(WildcardStatement::Equal(self.key.clone(), self.lower.operand.clone()));
+        }
+        targets
+    }
+
+    // The targets that satisfy the upper end: key < upper, or key = upper when inclusive
+    pub fn upper_targets(&self) -> Vec<WildcardStatement> {
+        let mut targets = vec![WildcardStatement::Lt(self.key.clone(), self.upper.operand.clone())];
+        if self.upper.inclusive {
+            targets.push(WildcardStatement::Equal(self.key.clone(), self.upper.operand.clone()));
+        }
+        targets
+    }
+}
+
+// One satisfying binding of a conjunctive query, with the proven statement for
+// each query statement (in query order) and a single combined chain
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryProof {
+    pub statements: Vec<HashableStatement>,
+    pub chain: DeductionChain,
+    pub bindings: Bindings,
+}
+
+// Cancels a bounded proving call from another thread. Clones share one flag.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+// Limits on a bounded proving call. A limit left as None is not enforced.
+#[derive(Debug, Clone, Default)]
+pub struct Budget {
+    pub deadline: Option<Instant>,
+    // Tuples across all of the program's relations, including input facts
+    pub max_tuples: Option<usize>,
+    // Proofs with longer chains are dropped from the results
+    pub max_chain_length: Option<usize>,
+    // Fixpoint iterations, summed over the program's strata
+    pub max_rounds: Option<usize>,
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Budget {
+    // A budget whose deadline is `timeout` from now
+    pub fn timeout(timeout: Duration) -> Self {
+        Self { deadline: Some(Instant::now() + timeout), ..Self::default() }
+    }
+}
+
+// The limit that stopped a bounded proving call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Deadline,
+    Tuples,
+    ChainLength,
+    Rounds,
+    Cancelled,
+}
+
+// The result of a bounded proving call. When a limit is hit, the partial result
+// holds whatever had been proven by then.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Bounded<T> {
+    Complete(T),
+    BudgetExceeded { limit: Limit, partial: T },
+}
+
+impl<T> Bounded<T> {
+    pub fn new(limit: Option<Limit>, value: T) -> Self {
+        match limit {
+            Some(limit) => Self::BudgetExceeded { limit, partial: value },
+            None => Self::Complete(value),
+        }
+    }
+
+    // The limit that was hit, if any
+    pub fn exceeded(&self) -> Option<Limit> {
+        match self {
+            Self::Complete(_) => None,
+            Self::BudgetExceeded { limit, .. } => Some(*limit),
+        }
+    }
+
+    pub fn value(&self) -> &T {
+        match self {
+            Self::Complete(value) | Self::BudgetExceeded { partial: value, .. } => value,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Complete(value) | Self::BudgetExceeded { partial: value, .. } => value,
+        }
+    }
+}
+
+// Counts the wildcard keys the rules compare against concrete keys. Each
+// program holds its own in the `match_counter` relation and the rules match
+// through it, so engines running at the same time, on any threads, never
+// share a count. Counters are only equal to themselves.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MatchCounter(Arc<AtomicUsize>);
+
+impl MatchCounter {
+    // `wildcard.matches(concrete)`, counted
+    pub(crate) fn matches(&self, wildcard: &WildcardAnchoredKey, concrete: &AnchoredKey) -> bool {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        wildcard.matches(concrete)
+    }
+
+    // `wildcard.matches_pair(..)`, counting each operand compared
+    pub(crate) fn matches_pair(
+        &self,
+        wildcard: &WildcardAnchoredKey,
+        other: &WildcardAnchoredKey,
+        concrete: &AnchoredKey,
+        other_concrete: &AnchoredKey,
+    ) -> bool {
+        self.matches(wildcard, concrete)
+            && self.matches(other, other_concrete)
+            && wildcard.binds_consistently(other, concrete, other_concrete)
+    }
+
+    // The matches counted since the last call
+    pub(crate) fn take(&self) -> usize {
+        self.0.swap(0, Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for MatchCounter {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for MatchCounter {}
+
+impl Hash for MatchCounter {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state)
+    }
+}
+
+// What the engine did during its last proving call
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProveStats {
+    // Tuples in each relation when the evaluation finished
+    pub relation_sizes: BTreeMap<&'static str, usize>,
+    // Fixpoint iterations, summed over the program's strata
+    pub rounds: usize,
+    // Running the rules
+    pub evaluation_time: Duration,
+    // Choosing derivations and building proofs from the evaluated relations
+    pub extraction_time: Duration,
+    // Wildcard keys compared against concrete keys while evaluating
+    pub candidate_matches: usize,
+    // Distinct keys and values the engine has interned, over all its facts and targets
+    pub interned_keys: usize,
+    pub interned_values: usize,
+}
+
+impl ProveStats {
+    // Tuples in a relation, or zero for a relation that doesn't exist
+    pub fn relation(&self, name: &str) -> usize {
+        self.relation_sizes.get(name).copied().unwrap_or(0)
+    }
+}
+
+impl fmt::Display for ProveStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "rounds: {}, candidate matches: {}", self.rounds, self.candidate_matches)?;
+        writeln!(f, "interned keys: {}, interned values: {}", self.interned_keys, self.interned_values)?;
+        writeln!(f, "evaluation: {:?}, extraction: {:?}", self.evaluation_time, self.extraction_time)?;
+        write!(f, "relations:")?;
+        for (name, size) in &self.relation_sizes {
+            write!(f, "\n  {}: {}", name, size)?;
+        }
+        Ok(())
+    }
+}
+
+// Why the engine could not produce proofs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProverError {
+    // The target is well formed but nothing in the fact base proves it
+    NoProof(WildcardStatement),
+    // The rules have no way to prove a target of this kind
+    UnsupportedTarget { target: WildcardStatement, reason: String },
+    // A fact (or a target operand's known value) of the wrong type for its statement
+    IllTypedFact { fact: HashableStatement, reason: String },
+    // Checking membership of a container failed, so a containment proof may be missing
+    ContainerLookup { key: AnchoredKey, reason: String },
+}
+
+impl fmt::Display for ProverError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoProof(target) => write!(f, "no proof exists for {:?}", target),
+            Self::UnsupportedTarget { target, reason } => write!(f, "unsupported target {:?}: {}", target, reason),
+            Self::IllTypedFact { fact, reason } => write!(f, "ill-typed fact {}: {}", fact, reason),
+            Self::ContainerLookup { key, reason } => write!(f, "lookup in container {} failed: {}", format_anchored_key(key), reason),
+        }
+    }
+}
+
+impl std::error::Error for ProverError {}
+
+impl WildcardStatement {
+    // Pair each wildcard operand with the concrete key it matched in `stmt`
+    fn operand_pairs<'a>(&'a self, stmt: &'a HashableStatement) -> Option<Vec<(&'a WildcardAnchoredKey, &'a AnchoredKey)>> {
+        match (self, stmt) {
+            (Self::ValueOf(w, _), HashableStatement::ValueOf(k, _)) => Some(vec![(w, k)]),
+            (Self::Equal(w1, w2), HashableStatement::Equal(k1, k2))
+            | (Self::NotEqual(w1, w2), HashableStatement::NotEqual(k1, k2))
+            | (Self::Gt(w1, w2), HashableStatement::Gt(k1, k2))
+            | (Self::Lt(w1, w2), HashableStatement::Lt(k1, k2))
+            | (Self::Contains(w1, w2), HashableStatement::Contains(k1, k2))
+            | (Self::NotContains(w1, w2), HashableStatement::NotContains(k1, k2)) => Some(vec![(w1, k1), (w2, k2)]),
+            (Self::SumOf(w, _, _), HashableStatement::SumOf(k, _, _))
+            | (Self::ProductOf(w, _, _), HashableStatement::ProductOf(k, _, _))
+            | (Self::MaxOf(w, _, _), HashableStatement::MaxOf(k, _, _)) => Some(vec![(w, k)]),
+            _ => None,
+        }
+    }
+
+    // The wildcard operands of this statement
+    pub fn operands(&self) -> Vec<WildcardAnchoredKey> {
+        match self {
+            Self::ValueOf(w, _)
+            | Self::SumOf(w, _, _)
+            | Self::ProductOf(w, _, _)
+            | Self::MaxOf(w, _, _) => vec![w.clone()],
+            Self::Equal(w1, w2)
+            | Self::NotEqual(w1, w2)
+            | Self::Gt(w1, w2)
+            | Self::Lt(w1, w2)
+            | Self::Contains(w1, w2)
+            | Self::NotContains(w1, w2) => vec![w1.clone(), w2.clone()],
+        }
+    }
+
+    // The bindings a proven statement assigns to this statement's named wildcards,
+    // or None if the statement does not fit or binds one name inconsistently
+    pub fn bindings(&self, stmt: &HashableStatement) -> Option<Bindings> {
+        let mut bindings = Bindings::new();
+        for (wild, concrete) in self.operand_pairs(stmt)? {
+            for (name, binding) in wild.bindings_for(concrete) {
+                if let Some(previous) = bindings.get(&name) {
+                    if previous.conflicts_with(&binding) {
+                        return None;
+                    }
+                } else {
+                    bindings.insert(name, binding);
+                }
+            }
+        }
+        Some(bindings)
+    }
 }
 
 // Helper methods for WildcardAnchoredKey
 impl WildcardAnchoredKey {
     pub fn concrete(origin: Origin, key: String) -> Self {
-        Self(WildcardId::Concrete(origin), key)
+        Self(WildcardId::Concrete(origin), KeyPattern::Exact(key))
     }
 
     pub fn wildcard(key: String, name: impl Into<String>) -> Self {
-        Self(WildcardId::Named(name.into()), key)
+        Self(WildcardId::Named(name.into()), KeyPattern::Exact(key))
     }
 
+    // A literal operand. The engine backs it with a self entry named after the
+    // value's type and contents, or commitment for a container, introduced by a
+    // NewEntry step in any proof that uses it.
+    pub fn literal(value: HashableValue) -> Self {
+        let key = match &value {
+            HashableValue::String(s) => format!("literal_string_{}", s),
+            HashableValue::Int(i) => format!("literal_int_{}", i),
+            HashableValue::Bool(b) => format!("literal_bool_{}", b),
+            HashableValue::Dictionary(d) => format!("literal_dictionary_{}", d.commitment()),
+            HashableValue::Set(s) => format!("literal_set_{}", s.commitment()),
+            HashableValue::Array(a) => format!("literal_array_{}", a.commitment()),
+        };
+        Self(WildcardId::Literal(value), KeyPattern::Exact(key))
+    }
+
+    // The concrete key this refers to, if neither the origin nor the key is a wildcard
+    pub fn as_concrete(&self) -> Option<AnchoredKey> {
+        match (&self.0, self.1.exact()) {
+            (WildcardId::Concrete(origin), Some(key)) => Some(AnchoredKey(origin.clone(), key)),
+            (WildcardId::Literal(_), Some(key)) => Some(AnchoredKey(self_origin(), key)),
+            _ => None,
+        }
+    }
+
+    // The bindings that matching `concrete` assigns to this key's wildcard names.
+    // The key name is only reported when the key pattern is not exact.
+    pub fn bindings_for(&self, concrete: &AnchoredKey) -> Vec<(String, Binding)> {
+        let key = match &self.1 {
+            KeyPattern::Exact(_) => None,
+            _ => Some(concrete.1.clone()),
+        };
+        let binding = |kind: BindingKind| Binding { origin: concrete.0.clone(), key: key.clone(), kind };
+        let mut bindings = Vec::new();
+        if let WildcardId::Named(name) = &self.0 {
+            bindings.push((name.clone(), binding(BindingKind::Origin)));
+        }
+        if let KeyPattern::Named(name) = &self.1 {
+            bindings.push((name.clone(), binding(BindingKind::Key)));
+        }
+        bindings
+    }
+
+    // Match both operands of a binary statement at once. A wildcard name used on
+    // both sides must bind consistently.
+    pub fn matches_pair(&self, other: &WildcardAnchoredKey, concrete: &AnchoredKey, other_concrete: &AnchoredKey) -> bool {
+        self.matches(concrete) && other.matches(other_concrete) && self.binds_consistently(other, concrete, other_concrete)
+    }
+
+    // Whether the names both operands bind agree, given that each matches
+    fn binds_consistently(&self, other: &WildcardAnchoredKey, concrete: &AnchoredKey, other_concrete: &AnchoredKey) -> bool {
+        let ours = self.bindings_for(concrete);
+        other.bindings_for(other_concrete).iter().all(|(name, binding)| {
+            ours.iter().all(|(our_name, our_binding)| our_name != name || !our_binding.conflicts_with(binding))
+        })
+    }
+
+    // Self entries only back literal operands, so only a literal (or a key
+    // naming the self origin outright) matches one
     pub fn matches(&self, concrete: &AnchoredKey) -> bool {
-        println!("Matching wildcard {:?} against concrete key {:?}", self, concrete);
-        let result = if let WildcardId::Concrete(origin) = &self.0 {
-            let matches = *origin == concrete.0 && self.1 == concrete.1;
-            println!("  Concrete match: origin {} == {} ? {}", origin.1.to_string(), concrete.0.1.to_string(), matches);
-            matches
-        } else if let WildcardId::Named(_) = &self.0 {
-            let matches = self.1 == concrete.1;
-            println!("  Named match: key {} == {} ? {}", self.1, concrete.1, matches);
-            matches
-        } else {
-            false
+        let result = match &self.0 {
+            WildcardId::Concrete(origin) => *origin == concrete.0 && self.1.matches(&concrete.1),
+            WildcardId::Named(_) => concrete.0 != self_origin() && self.1.matches(&concrete.1),
+            WildcardId::Literal(_) => self.as_concrete().as_ref() == Some(concrete),
         };
-        println!("  Final result: {}", result);
+        trace!(wildcard = ?self, concrete = ?concrete, result, "matched wildcard key");
         result
     }
 }
+
+impl From<AnchoredKey> for WildcardAnchoredKey {
+    fn from(key: AnchoredKey) -> Self {
+        Self(WildcardId::Concrete(key.0), KeyPattern::Exact(key.1))
+    }
+}
@@ -0,0 +1,167 @@
+use crate::engine::{check_contains, check_not_contains, to_value};
+use crate::types::{DeductionStep, HashableStatement, HashableValue, Operation, WildcardAnchoredKey};
+use pod2::middleware::NativeOperation;
+use std::collections::HashSet;
+use std::fmt;
+
+// The native operations a chain may use
+const OPERATIONS: [u8; 14] = [
+    NativeOperation::NewEntry as u8,
+    NativeOperation::CopyStatement as u8,
+    NativeOperation::EqualFromEntries as u8,
+    NativeOperation::NotEqualFromEntries as u8,
+    NativeOperation::GtFromEntries as u8,
+    NativeOperation::LtFromEntries as u8,
+    NativeOperation::TransitiveEqualFromStatements as u8,
+    NativeOperation::GtToNotEqual as u8,
+    NativeOperation::LtToNotEqual as u8,
+    NativeOperation::ContainsFromEntries as u8,
+    NativeOperation::NotContainsFromEntries as u8,
+    NativeOperation::SumOf as u8,
+    NativeOperation::ProductOf as u8,
+    NativeOperation::MaxOf as u8,
+];
+
+// The first thing wrong with a chain. Steps are numbered from zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainError {
+    // The op code is not an operation a chain may use
+    UnknownOperation { step: usize, op_code: u8 },
+    // The step is one of the engine's own, which only `verify_engine_chain` accepts
+    NonNativeOperation { step: usize, operation: &'static str },
+    // The inputs or output don't have the shape the operation takes
+    MalformedStep { step: usize, operation: &'static str },
+    // An input is not the output of an earlier step, or a copy of something
+    // that isn't a known fact
+    UnavailableInput { step: usize, input: HashableStatement },
+    // The input values don't satisfy the operation
+    UnsatisfiedStep { step: usize, operation: &'static str },
+    // The claimed statement is not the output of any step
+    Unproven(HashableStatement),
+}
+
+impl fmt::Display for ChainError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownOperation { step, op_code } => write!(f, "step {}: unknown operation {}", step, op_code),
+            Self::NonNativeOperation { step, operation } => write!(f, "step {}: {} is not a native operation", step, operation),
+            Self::MalformedStep { step, operation } => write!(f, "step {}: malformed {} step", step, operation),
+            Self::UnavailableInput { step, input } => write!(f, "step {}: input {} is not established", step, input),
+            Self::UnsatisfiedStep { step, operation } => write!(f, "step {}: values do not satisfy {}", step, operation),
+            Self::Unproven(claimed) => write!(f, "{} is not established by the chain", claimed),
+        }
+    }
+}
+
+impl std::error::Error for ChainError {}
+
+// Check that `chain` proves `claimed` from `facts` by replaying every step,
+// independently of the engine's rules. Copies must copy known facts, every
+// other step may only use the outputs of earlier steps, and each step's values
+// must satisfy its operation. Only native operations are accepted, so a chain
+// that checks is one a pod can replay. Statements must match exactly.
+pub fn verify_chain(facts: &[HashableStatement], chain: &[DeductionStep], claimed: &HashableStatement) -> Result<(), ChainError> {
+    verify(facts, chain, claimed, false)
+}
+
+// Like `verify_chain`, but also accept the engine's own steps: an equality used
+// the other way round may be flipped by `Operation::SymmetricEqual`. Engine
+// proofs can contain such steps; a pod cannot replay them.
+pub fn verify_engine_chain(facts: &[HashableStatement], chain: &[DeductionStep], claimed: &HashableStatement) -> Result<(), ChainError> {
+    verify(facts, chain, claimed, true)
+}
+
+fn verify(facts: &[HashableStatement], chain: &[DeductionStep], claimed: &HashableStatement, engine_steps: bool) -> Result<(), ChainError> {
+    let facts: HashSet<&HashableStatement> = facts.iter().collect();
+    let mut established = HashSet::new();
+    for (step, (op_code, inputs, output)) in chain.iter().enumerate() {
+        verify_step(step, *op_code, inputs, output, &facts, &established, engine_steps)?;
+        established.insert(output);
+    }
+    if !established.contains(claimed) {
+        return Err(ChainError::Unproven(claimed.clone()));
+    }
+    Ok(())
+}
+
+fn int(value: &HashableValue) -> Option<i64> {
+    match value {
+        HashableValue::Int(i) => Some(*i),
+        _ => None,
+    }
+}
+
+fn verify_step(
+    step: usize,
+    op_code: u8,
+    inputs: &[HashableStatement],
+    output: &HashableStatement,
+    facts: &HashSet<&HashableStatement>,
+    established: &HashSet<&HashableStatement>,
+    engine_steps: bool,
+) -> Result<(), ChainError> {
+    let kind = Operation::from_code(op_code).filter(|kind| !kind.is_native() || OPERATIONS.contains(&op_code));
+    let Some(kind) = kind else {
+        return Err(ChainError::UnknownOperation { step, op_code });
+    };
+    let operation = kind.name();
+    if !kind.is_native() && !engine_steps {
+        return Err(ChainError::NonNativeOperation { step, operation });
+    }
+    let malformed = ChainError::MalformedStep { step, operation };
+
+    // Copies are the only way facts enter a chain, so a fact must be copied
+    // before another step can use it
+    if op_code == NativeOperation::CopyStatement as u8 {
+        return match inputs {
+            [input] if input == output && facts.contains(input) => Ok(()),
+            [input] if input == output => Err(ChainError::UnavailableInput { step, input: input.clone() }),
+            _ => Err(malformed),
+        };
+    }
+    if let Some(input) = inputs.iter().find(|input| !established.contains(input)) {
+        return Err(ChainError::UnavailableInput { step, input: input.clone() });
+    }
+
+    use HashableStatement::*;
+    let is = |operation: NativeOperation| op_code == operation as u8;
+    let satisfied = match (inputs, output) {
+        // A new entry of the pod being built, holding a literal operand
+        ([], ValueOf(key, value)) if is(NativeOperation::NewEntry) => {
+            WildcardAnchoredKey::literal(value.clone()).as_concrete().as_ref() == Some(key)
+        },
+        ([ValueOf(a, v1), ValueOf(b, v2)], Equal(x, y)) if is(NativeOperation::EqualFromEntries) && (a, b) == (x, y) => to_value(v1) == to_value(v2),
+        ([ValueOf(a, v1), ValueOf(b, v2)], NotEqual(x, y)) if is(NativeOperation::NotEqualFromEntries) && (a, b) == (x, y) => to_value(v1) != to_value(v2),
+        ([ValueOf(a, v1), ValueOf(b, v2)], Gt(x, y)) if is(NativeOperation::GtFromEntries) && (a, b) == (x, y) => {
+            matches!((int(v1), int(v2)), (Some(i1), Some(i2)) if i1 > i2)
+        },
+        ([ValueOf(a, v1), ValueOf(b, v2)], Lt(x, y)) if is(NativeOperation::LtFromEntries) && (a, b) == (x, y) => {
+            matches!((int(v1), int(v2)), (Some(i1), Some(i2)) if i1 < i2)
+        },
+        ([ValueOf(a, v1), ValueOf(b, v2)], Contains(x, y)) if is(NativeOperation::ContainsFromEntries) && (a, b) == (x, y) => {
+            check_contains(v1, v2) == Ok(true)
+        },
+        ([ValueOf(a, v1), ValueOf(b, v2)], NotContains(x, y)) if is(NativeOperation::NotContainsFromEntries) && (a, b) == (x, y) => {
+            check_not_contains(v1, v2) == Ok(true)
+        },
+        ([Equal(a, b), Equal(c, d)], Equal(x, z)) if is(NativeOperation::TransitiveEqualFromStatements) && a == x && d == z => b == c,
+        ([Equal(a, b)], Equal(x, y)) if kind == Operation::SymmetricEqual => (a, b) == (y, x),
+        ([Gt(a, b)], NotEqual(x, y)) if is(NativeOperation::GtToNotEqual) => (a, b) == (x, y),
+        ([Lt(a, b)], NotEqual(x, y)) if is(NativeOperation::LtToNotEqual) => (a, b) == (x, y),
+        ([ValueOf(a, v1), ValueOf(b, v2), ValueOf(c, v3)], SumOf(x, y, z)) if is(NativeOperation::SumOf) && (a, b, c) == (x, y, z) => {
+            matches!((int(v1), int(v2), int(v3)), (Some(i1), Some(i2), Some(i3)) if i2.checked_add(i3) == Some(i1))
+        },
+        ([ValueOf(a, v1), ValueOf(b, v2), ValueOf(c, v3)], ProductOf(x, y, z)) if is(NativeOperation::ProductOf) && (a, b, c) == (x, y, z) => {
+            matches!((int(v1), int(v2), int(v3)), (Some(i1), Some(i2), Some(i3)) if i2.checked_mul(i3) == Some(i1))
+        },
+        ([ValueOf(a, v1), ValueOf(b, v2), ValueOf(c, v3)], MaxOf(x, y, z)) if is(NativeOperation::MaxOf) && (a, b, c) == (x, y, z) => {
+            matches!((int(v1), int(v2), int(v3)), (Some(i1), Some(i2), Some(i3)) if i1 == i2.max(i3))
+        },
+        _ => return Err(malformed),
+    };
+    if satisfied {
+        Ok(())
+    } else {
+        Err(ChainError::UnsatisfiedStep { step, operation })
+    }
+}